@@ -1,6 +1,6 @@
 #![warn(clippy::pedantic)]
-use basket::RequestBuilder;
-use clap::{Parser, ValueEnum, ValueHint};
+use basket::{Method, RequestBuilder};
+use clap::{Parser, ValueHint};
 
 /// A simple HTTP client.
 ///
@@ -13,7 +13,8 @@ use clap::{Parser, ValueEnum, ValueHint};
 /// basket post <http://example.com> --json --data '{\"message\": \"Hello, world!\"}'
 #[derive(Parser)]
 struct Args {
-	#[arg(value_name = "METHOD")]
+	/// Any method, such as GET, HEAD or PROPFIND. Standard methods are case-insensitive.
+	#[arg(value_name = "METHOD", value_parser = parse_method)]
 	method: Method,
 	#[arg(value_name = "URL", value_hint = ValueHint::Url)]
 	url: String,
@@ -38,32 +39,18 @@ impl From<String> for Header {
 	}
 }
 
-#[derive(Clone, Copy, ValueEnum)]
-enum Method {
-	Delete,
-	Get,
-	Options,
-	Patch,
-	Post,
-	Put,
-}
-
-impl From<Method> for basket::request::Method {
-	fn from(value: Method) -> Self {
-		match value {
-			Method::Delete => Self::Delete,
-			Method::Get => Self::Get,
-			Method::Options => Self::Options,
-			Method::Patch => Self::Patch,
-			Method::Post => Self::Post,
-			Method::Put => Self::Put,
-		}
+fn parse_method(value: &str) -> Result<Method, String> {
+	// extension methods are case-sensitive, so only standard methods are uppercased
+	match value.to_ascii_uppercase().parse() {
+		Ok(Method::Extension(_)) | Err(_) => value.parse(),
+		standard => standard,
 	}
+	.map_err(|_| format!("`{value}` is not a valid method"))
 }
 
 fn main() {
 	let args = Args::parse();
-	let mut request = RequestBuilder::new(args.method.into(), args.url.as_str());
+	let mut request = RequestBuilder::new(args.method, args.url.as_str());
 
 	if let Some(data) = args.data {
 		if args.json {
@@ -77,7 +64,7 @@ fn main() {
 	}
 
 	for header in args.headers {
-		request = request.header((header.0, header.1));
+		request = request.append_header((header.0, header.1));
 	}
 
 	let response = request.send().expect("could not send request");
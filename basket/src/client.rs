@@ -0,0 +1,336 @@
+#[cfg(unix)]
+use std::path::PathBuf;
+use std::{sync::Arc, thread};
+
+use url::{ParseError, Url};
+
+#[cfg(unix)]
+use crate::UnixConnector;
+#[cfg(feature = "cookies")]
+use crate::{cookie::CookieJar, header};
+use crate::{
+	Connector, Error, Method, ParseConfig, Proxy, Request, RequestBuilder, Response, RetryPolicy,
+	TcpConnector,
+};
+
+/// A reusable HTTP client that holds configuration shared between requests.
+#[must_use]
+#[derive(Debug, Clone, Default)]
+pub struct Client {
+	#[cfg(feature = "cookies")]
+	cookie_jar: Option<Arc<CookieJar>>,
+	parse_config: Option<ParseConfig>,
+	error_for_status: bool,
+	retry: Option<RetryPolicy>,
+	proxies: Vec<Proxy>,
+	connector: Option<Arc<dyn Connector>>,
+	#[cfg(unix)]
+	unix_socket: Option<PathBuf>,
+}
+
+impl Client {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Stores cookies from responses in the jar, and sends matching
+	/// cookies with every request.
+	///
+	/// The jar can be shared between clients, and saved with [`CookieJar::save`].
+	#[cfg(feature = "cookies")]
+	pub fn cookie_jar(mut self, jar: Arc<CookieJar>) -> Self {
+		self.cookie_jar = Some(jar);
+		self
+	}
+
+	/// Sets the options used to parse responses, such as the maximum body size.
+	///
+	/// Folded headers are rejected unless enabled with [`ParseConfig::obs_fold`].
+	pub fn parse_config(mut self, config: ParseConfig) -> Self {
+		self.parse_config = Some(config);
+		self
+	}
+
+	/// Returns an [`Error::Status`] for every response that is not 2xx,
+	/// as if [`Response::error_for_status`] was called on it.
+	///
+	/// Cookies from the response are still stored before the error is returned.
+	pub fn error_for_status(mut self, enabled: bool) -> Self {
+		self.error_for_status = enabled;
+		self
+	}
+
+	/// Resends requests that fail with a transient error, such as a reset
+	/// connection or a 503 response. Requests are sent once by default.
+	pub fn retry(mut self, policy: RetryPolicy) -> Self {
+		self.retry = Some(policy);
+		self
+	}
+
+	/// Sends requests through the proxy. Proxies are checked in the order
+	/// they were added, and the first one that applies to the URL is used.
+	pub fn proxy(mut self, proxy: Proxy) -> Self {
+		self.proxies.push(proxy);
+		self
+	}
+
+	/// Sends requests to the Unix socket at the path, instead of the host and
+	/// port of their URLs, which are still used for the `host` header.
+	///
+	/// This is the only way to reach a Unix socket, since URLs such as
+	/// `unix:/run/app.sock` are not supported. Proxies are not used for
+	/// requests sent to a Unix socket.
+	#[cfg(unix)]
+	pub fn unix_socket<P: Into<PathBuf>>(mut self, path: P) -> Self {
+		let path = path.into();
+
+		self.connector = Some(Arc::new(UnixConnector::new(path.clone())));
+		self.unix_socket = Some(path);
+		self
+	}
+
+	/// Opens the connections for blocking requests with the connector,
+	/// instead of connecting over TCP. Replaces any Unix socket set with
+	/// [`Client::unix_socket`].
+	///
+	/// Requests sent with [`RequestBuilder::send_async`] fail with
+	/// [`Error::UnsupportedConnector`].
+	pub fn connector<C>(mut self, connector: C) -> Self
+	where
+		C: Connector + 'static,
+	{
+		self.connector = Some(Arc::new(connector));
+
+		#[cfg(unix)]
+		{
+			self.unix_socket = None;
+		}
+
+		self
+	}
+
+	/// Sends requests through the proxies set in the environment, as
+	/// returned by [`Proxy::from_env`].
+	pub fn proxy_from_env(mut self) -> Self {
+		self.proxies.extend(Proxy::from_env());
+		self
+	}
+
+	pub fn request<'h, U: TryInto<Url, Error = ParseError>>(
+		&self,
+		method: Method,
+		url: U,
+	) -> RequestBuilder<'h> {
+		RequestBuilder::new(method, url).client(self.clone())
+	}
+
+	pub fn delete<'h, U: TryInto<Url, Error = ParseError>>(&self, url: U) -> RequestBuilder<'h> {
+		self.request(Method::Delete, url)
+	}
+
+	pub fn get<'h, U: TryInto<Url, Error = ParseError>>(&self, url: U) -> RequestBuilder<'h> {
+		self.request(Method::Get, url)
+	}
+
+	pub fn head<'h, U: TryInto<Url, Error = ParseError>>(&self, url: U) -> RequestBuilder<'h> {
+		self.request(Method::Head, url)
+	}
+
+	pub fn options<'h, U: TryInto<Url, Error = ParseError>>(&self, url: U) -> RequestBuilder<'h> {
+		self.request(Method::Options, url)
+	}
+
+	pub fn patch<'h, U: TryInto<Url, Error = ParseError>>(&self, url: U) -> RequestBuilder<'h> {
+		self.request(Method::Patch, url)
+	}
+
+	pub fn post<'h, U: TryInto<Url, Error = ParseError>>(&self, url: U) -> RequestBuilder<'h> {
+		self.request(Method::Post, url)
+	}
+
+	pub fn put<'h, U: TryInto<Url, Error = ParseError>>(&self, url: U) -> RequestBuilder<'h> {
+		self.request(Method::Put, url)
+	}
+
+	/// Sends the request with the client's configuration and returns the response.
+	///
+	/// # Errors
+	/// - If the request could not be sent.
+	/// - If the response could not be read.
+	pub fn execute<'h>(&self, mut request: Request<'h>) -> Result<Response<'h>, Error> {
+		let retry = self.retry_policy(&request);
+		let mut attempt = 1;
+
+		#[cfg(feature = "cookies")]
+		let cookie = request.header(header::COOKIE).map(str::to_string);
+
+		let response = loop {
+			#[cfg(feature = "cookies")]
+			self.add_cookies(&mut request, cookie.as_deref());
+
+			let result = self.send(&mut request);
+
+			match retry.and_then(|policy| policy.delay(attempt, &result)) {
+				Some(delay) => thread::sleep(delay),
+				None => break result?,
+			}
+
+			attempt += 1;
+		};
+
+		if self.error_for_status {
+			return response.error_for_status();
+		}
+
+		Ok(response)
+	}
+
+	/// Sends the request with the client's configuration without blocking
+	/// and returns the response.
+	///
+	/// # Errors
+	/// - If the client has a custom connector.
+	/// - If the request could not be sent.
+	/// - If the response could not be read.
+	#[cfg(feature = "async")]
+	pub async fn execute_async<'h>(&self, mut request: Request<'h>) -> Result<Response<'h>, Error> {
+		if self.has_custom_connector() {
+			return Err(Error::UnsupportedConnector);
+		}
+
+		let retry = self.retry_policy(&request);
+		let mut attempt = 1;
+
+		#[cfg(feature = "cookies")]
+		let cookie = request.header(header::COOKIE).map(str::to_string);
+
+		let response = loop {
+			#[cfg(feature = "cookies")]
+			self.add_cookies(&mut request, cookie.as_deref());
+
+			let result = self.send_async(&mut request).await;
+
+			match retry.and_then(|policy| policy.delay(attempt, &result)) {
+				Some(delay) => tokio::time::sleep(delay).await,
+				None => break result?,
+			}
+
+			attempt += 1;
+		};
+
+		if self.error_for_status {
+			return response.error_for_status();
+		}
+
+		Ok(response)
+	}
+
+	/// Returns the retry policy if it applies to the request, which must
+	/// have a body that can be sent again.
+	fn retry_policy(&self, request: &Request) -> Option<&RetryPolicy> {
+		self.retry
+			.as_ref()
+			.filter(|policy| !request.has_stream() && policy.allows(&request.method))
+	}
+
+	/// Returns whether connections are opened by a connector set with
+	/// [`Client::connector`], rather than over TCP or a Unix socket.
+	#[cfg(feature = "async")]
+	fn has_custom_connector(&self) -> bool {
+		#[cfg(unix)]
+		if self.unix_socket.is_some() {
+			return false;
+		}
+
+		self.connector.is_some()
+	}
+
+	fn config(&self) -> ParseConfig {
+		self.parse_config
+			.clone()
+			.unwrap_or_else(|| ParseConfig::default().obs_fold(true))
+	}
+
+	fn proxy_for(&self, url: &Url) -> Option<&Proxy> {
+		#[cfg(unix)]
+		if self.unix_socket.is_some() {
+			return None;
+		}
+
+		self.proxies.iter().find(|proxy| proxy.intercepts(url))
+	}
+
+	/// Sends the request once, storing cookies from the response.
+	fn send<'h>(&self, request: &mut Request<'h>) -> Result<Response<'h>, Error> {
+		let config = self.config();
+
+		let connector = self.connector.as_deref().unwrap_or(&TcpConnector);
+		let response = request.send_with(&config, connector, self.proxy_for(&request.url))?;
+
+		#[cfg(feature = "cookies")]
+		self.store_cookies(&request.url, &response);
+
+		Ok(response)
+	}
+
+	/// Sends the request once without blocking, storing cookies from the response.
+	#[cfg(feature = "async")]
+	async fn send_async<'h>(&self, request: &mut Request<'h>) -> Result<Response<'h>, Error> {
+		let config = self.config();
+
+		#[cfg(unix)]
+		let response = match &self.unix_socket {
+			Some(path) => request.send_unix_async(path, &config).await?,
+			None => {
+				request
+					.send_async_with(&config, self.proxy_for(&request.url))
+					.await?
+			}
+		};
+		#[cfg(not(unix))]
+		let response = request
+			.send_async_with(&config, self.proxy_for(&request.url))
+			.await?;
+
+		#[cfg(feature = "cookies")]
+		self.store_cookies(&request.url, &response);
+
+		Ok(response)
+	}
+
+	/// Sets the cookie header to the one that was set manually, if any, and
+	/// the matching cookies from the jar, which may have changed since the
+	/// last attempt.
+	#[cfg(feature = "cookies")]
+	fn add_cookies(&self, request: &mut Request, existing: Option<&str>) {
+		let cookies = self
+			.cookie_jar
+			.as_ref()
+			.and_then(|jar| jar.header(&request.url));
+
+		// there can only be one cookie header, so merge with any that was set manually
+		let cookies = match (existing, cookies) {
+			(Some(existing), Some(cookies)) => format!("{existing}; {cookies}"),
+			(Some(cookies), None) => cookies.to_string(),
+			(None, Some(cookies)) => cookies,
+			(None, None) => {
+				request.headers.remove(header::COOKIE);
+
+				return;
+			}
+		};
+
+		request.headers.insert((header::COOKIE, cookies));
+	}
+
+	#[cfg(feature = "cookies")]
+	fn store_cookies(&self, url: &Url, response: &Response) {
+		let Some(jar) = &self.cookie_jar else {
+			return;
+		};
+
+		for set_cookie in response.headers.get_all(header::SET_COOKIE) {
+			jar.set_cookie(url, set_cookie);
+		}
+	}
+}
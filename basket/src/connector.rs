@@ -0,0 +1,82 @@
+use std::{
+	fmt,
+	io::{Read, Write},
+	net::TcpStream,
+};
+#[cfg(unix)]
+use std::{os::unix::net::UnixStream, path::PathBuf};
+
+use url::Url;
+
+use crate::Error;
+
+/// A stream that a request is written to and its response is read from.
+pub trait Connection: Read + Write + Send {}
+
+impl<T> Connection for T where T: Read + Write + Send {}
+
+/// Opens the connections that a [`Client`](crate::Client) sends blocking
+/// requests over, set with [`Client::connector`](crate::Client::connector).
+///
+/// A connector can wrap the stream in TLS, reach the host some other way, or
+/// return an in-memory stream for tests. Proxies of the client are connected
+/// to with the connector as well, using the URL of the proxy, and a tunnel
+/// opened through a proxy is passed to [`Connector::wrap`] with the URL of
+/// the request.
+pub trait Connector: fmt::Debug + Send + Sync {
+	/// Opens a connection to the host and port of the URL.
+	///
+	/// # Errors
+	/// - If the connection could not be opened.
+	fn connect(&self, url: &Url) -> Result<Box<dyn Connection>, Error>;
+
+	/// Wraps a stream that a proxy tunnelled to the host and port of the URL,
+	/// such as to start a TLS session with that host rather than the proxy.
+	///
+	/// By default, the stream is returned unchanged for an `http` URL, and an
+	/// `https` URL fails rather than being sent in plaintext.
+	///
+	/// # Errors
+	/// - If the URL is `https`, unless overridden.
+	/// - If the stream could not be wrapped.
+	fn wrap(&self, stream: Box<dyn Connection>, url: &Url) -> Result<Box<dyn Connection>, Error> {
+		if url.scheme() == "https" {
+			return Err(Error::PlaintextTunnel);
+		}
+
+		Ok(stream)
+	}
+}
+
+/// Connects over TCP to the host and port of the URL, which is the default.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TcpConnector;
+
+impl Connector for TcpConnector {
+	fn connect(&self, url: &Url) -> Result<Box<dyn Connection>, Error> {
+		let stream = TcpStream::connect(url.socket_addrs(|| None)?.as_slice())?;
+
+		Ok(Box::new(stream))
+	}
+}
+
+/// Connects to a Unix socket, whatever the host and port of the URL.
+#[cfg(unix)]
+#[derive(Debug, Clone)]
+pub struct UnixConnector {
+	path: PathBuf,
+}
+
+#[cfg(unix)]
+impl UnixConnector {
+	pub fn new<P: Into<PathBuf>>(path: P) -> Self {
+		Self { path: path.into() }
+	}
+}
+
+#[cfg(unix)]
+impl Connector for UnixConnector {
+	fn connect(&self, _: &Url) -> Result<Box<dyn Connection>, Error> {
+		Ok(Box::new(UnixStream::connect(&self.path)?))
+	}
+}
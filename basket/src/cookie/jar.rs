@@ -0,0 +1,359 @@
+use std::{
+	fmt::Write as _,
+	fs,
+	path::Path,
+	sync::{Mutex, MutexGuard, PoisonError},
+	time::{Duration, SystemTime},
+};
+
+use url::{Host, Url};
+
+use crate::Error;
+
+use super::Cookie;
+
+/// Multi-label public suffixes that cookies must not be scoped to.
+///
+/// Single-label domains (such as `com`) are always treated as public suffixes,
+/// and more can be added with [`CookieJar::public_suffix`].
+const PUBLIC_SUFFIXES: &[&str] = &[
+	"ac.uk",
+	"co.uk",
+	"gov.uk",
+	"org.uk",
+	"com.au",
+	"net.au",
+	"org.au",
+	"co.jp",
+	"co.nz",
+	"co.in",
+	"com.br",
+	"com.cn",
+	"com.mx",
+	"github.io",
+	"gitlab.io",
+	"herokuapp.com",
+	"netlify.app",
+	"pages.dev",
+	"vercel.app",
+];
+
+const NETSCAPE_HEADER: &str = "# Netscape HTTP Cookie File";
+const HTTP_ONLY_PREFIX: &str = "#HttpOnly_";
+
+#[derive(Debug, Clone)]
+struct StoredCookie {
+	name: String,
+	value: String,
+	domain: String,
+	host_only: bool,
+	path: String,
+	expires: Option<SystemTime>,
+	secure: bool,
+	http_only: bool,
+	created: SystemTime,
+}
+
+impl StoredCookie {
+	fn is_expired(&self, now: SystemTime) -> bool {
+		self.expires.is_some_and(|expires| expires <= now)
+	}
+}
+
+/// A thread-safe cookie store, following the storage and retrieval rules
+/// of RFC 6265.
+///
+/// Cookies can be persisted to and loaded from a file in the Netscape
+/// cookie file format used by curl and wget.
+#[derive(Debug, Default)]
+pub struct CookieJar {
+	cookies: Mutex<Vec<StoredCookie>>,
+	public_suffixes: Vec<String>,
+}
+
+impl CookieJar {
+	#[must_use]
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Treats the domain as a public suffix, in addition to the built-in list.
+	///
+	/// A leading `*.` matches any single label, so `*.example` makes
+	/// every `<name>.example` a public suffix.
+	#[must_use]
+	pub fn public_suffix<S: Into<String>>(mut self, suffix: S) -> Self {
+		let mut suffix = suffix.into();
+
+		suffix.make_ascii_lowercase();
+		self.public_suffixes.push(suffix);
+		self
+	}
+
+	/// Returns whether the domain is a public suffix.
+	#[must_use]
+	pub fn is_public_suffix(&self, domain: &str) -> bool {
+		if !domain.contains('.') {
+			return true;
+		}
+
+		PUBLIC_SUFFIXES
+			.iter()
+			.copied()
+			.chain(self.public_suffixes.iter().map(String::as_str))
+			.any(|suffix| match suffix.strip_prefix("*.") {
+				Some(parent) => domain
+					.split_once('.')
+					.is_some_and(|(_, rest)| rest.eq_ignore_ascii_case(parent)),
+				None => domain.eq_ignore_ascii_case(suffix),
+			})
+	}
+
+	/// Stores a cookie as if it were received in a response from the URL.
+	///
+	/// Cookies that the URL is not allowed to set are ignored, and cookies
+	/// that have already expired remove any matching cookie from the jar.
+	pub fn store(&self, url: &Url, cookie: Cookie) {
+		let Some(host) = url.host_str() else {
+			return;
+		};
+
+		let host = host.to_ascii_lowercase();
+		let now = SystemTime::now();
+
+		let (domain, host_only) = match cookie.domain.as_deref() {
+			Some(domain) if self.is_public_suffix(domain) => {
+				if domain != host {
+					return;
+				}
+
+				(host, true)
+			}
+			Some(domain) if domain_matches(url, &host, domain) => (domain.to_string(), false),
+			Some(_) => return,
+			None => (host, true),
+		};
+
+		// secure cookies can only be set by secure origins
+		if cookie.secure && !is_secure(url) {
+			return;
+		}
+
+		let expires = cookie.expiry(now);
+		let stored = StoredCookie {
+			path: cookie.path.unwrap_or_else(|| default_path(url.path())),
+			name: cookie.name,
+			value: cookie.value,
+			domain,
+			host_only,
+			expires,
+			secure: cookie.secure,
+			http_only: cookie.http_only,
+			created: now,
+		};
+
+		let mut cookies = self.lock();
+		let existing = cookies.iter().position(|c| {
+			c.name == stored.name && c.domain == stored.domain && c.path == stored.path
+		});
+
+		let created = existing.map_or(now, |index| cookies.remove(index).created);
+
+		if stored.is_expired(now) {
+			return;
+		}
+
+		cookies.push(StoredCookie { created, ..stored });
+	}
+
+	/// Parses a `set-cookie` header value and stores the cookie.
+	///
+	/// Invalid cookies are ignored.
+	pub fn set_cookie(&self, url: &Url, set_cookie: &str) {
+		if let Ok(cookie) = Cookie::parse(set_cookie) {
+			self.store(url, cookie);
+		}
+	}
+
+	/// Returns the value of the `cookie` header to send to the URL, if
+	/// any cookies match it.
+	#[must_use]
+	pub fn header(&self, url: &Url) -> Option<String> {
+		let host = url.host_str()?.to_ascii_lowercase();
+		let secure = is_secure(url);
+		let now = SystemTime::now();
+
+		let mut cookies = self.lock();
+
+		cookies.retain(|cookie| !cookie.is_expired(now));
+
+		let mut matching = cookies
+			.iter()
+			.filter(|cookie| {
+				if cookie.host_only {
+					cookie.domain == host
+				} else {
+					domain_matches(url, &host, &cookie.domain)
+				}
+			})
+			.filter(|cookie| path_matches(url.path(), &cookie.path))
+			.filter(|cookie| secure || !cookie.secure)
+			.collect::<Vec<_>>();
+
+		if matching.is_empty() {
+			return None;
+		}
+
+		// cookies with longer paths are listed first, then the oldest ones
+		matching.sort_by(|a, b| {
+			b.path
+				.len()
+				.cmp(&a.path.len())
+				.then(a.created.cmp(&b.created))
+		});
+
+		let mut header = String::new();
+
+		for cookie in matching {
+			if !header.is_empty() {
+				header.push_str("; ");
+			}
+
+			let _ = write!(header, "{}={}", cookie.name, cookie.value);
+		}
+
+		Some(header)
+	}
+
+	/// Removes all cookies from the jar.
+	pub fn clear(&self) {
+		self.lock().clear();
+	}
+
+	/// Loads cookies from a file in the Netscape cookie file format.
+	///
+	/// # Errors
+	/// - If the file could not be read.
+	/// - If a line in the file is not a valid cookie entry.
+	pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+		let content = fs::read_to_string(path)?;
+		let now = SystemTime::now();
+		let mut cookies = Vec::new();
+
+		for line in content.lines() {
+			let (line, http_only) = match line.strip_prefix(HTTP_ONLY_PREFIX) {
+				Some(line) => (line, true),
+				None => (line, false),
+			};
+
+			if line.trim().is_empty() || line.starts_with('#') {
+				continue;
+			}
+
+			let fields = line.split('\t').collect::<Vec<_>>();
+			let [domain, include_subdomains, path, secure, expires, name, value] = fields[..]
+			else {
+				return Err(Error::InvalidCookie);
+			};
+
+			let expires = match expires.parse::<u64>()? {
+				0 => None,
+				secs => Some(SystemTime::UNIX_EPOCH + Duration::from_secs(secs)),
+			};
+
+			let cookie = StoredCookie {
+				name: name.to_string(),
+				value: value.to_string(),
+				domain: domain.trim_start_matches('.').to_ascii_lowercase(),
+				host_only: include_subdomains != "TRUE",
+				path: path.to_string(),
+				expires,
+				secure: secure == "TRUE",
+				http_only,
+				created: now,
+			};
+
+			if !cookie.is_expired(now) {
+				cookies.push(cookie);
+			}
+		}
+
+		Ok(Self {
+			cookies: Mutex::new(cookies),
+			public_suffixes: Vec::new(),
+		})
+	}
+
+	/// Saves all cookies to a file in the Netscape cookie file format.
+	///
+	/// Session cookies are saved with an expiry of `0`, like curl does.
+	///
+	/// # Errors
+	/// - If the file could not be written.
+	pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+		let now = SystemTime::now();
+		let mut content = format!("{NETSCAPE_HEADER}\n");
+
+		for cookie in self.lock().iter().filter(|c| !c.is_expired(now)) {
+			let expires = cookie
+				.expires
+				.and_then(|expires| expires.duration_since(SystemTime::UNIX_EPOCH).ok())
+				.map_or(0, |expires| expires.as_secs());
+
+			let _ =
+				writeln!(
+				content,
+				"{prefix}{dot}{domain}\t{subdomains}\t{path}\t{secure}\t{expires}\t{name}\t{value}",
+				prefix = if cookie.http_only { HTTP_ONLY_PREFIX } else { "" },
+				dot = if cookie.host_only { "" } else { "." },
+				domain = cookie.domain,
+				subdomains = if cookie.host_only { "FALSE" } else { "TRUE" },
+				path = cookie.path,
+				secure = if cookie.secure { "TRUE" } else { "FALSE" },
+				name = cookie.name,
+				value = cookie.value,
+			);
+		}
+
+		fs::write(path, content)?;
+
+		Ok(())
+	}
+
+	fn lock(&self) -> MutexGuard<'_, Vec<StoredCookie>> {
+		self.cookies.lock().unwrap_or_else(PoisonError::into_inner)
+	}
+}
+
+fn is_secure(url: &Url) -> bool {
+	matches!(url.scheme(), "https" | "wss")
+}
+
+/// Returns whether the host domain-matches the cookie domain, as per RFC 6265 section 5.1.3.
+fn domain_matches(url: &Url, host: &str, domain: &str) -> bool {
+	if host == domain {
+		return true;
+	}
+
+	// ip addresses can only match exactly
+	matches!(url.host(), Some(Host::Domain(_)))
+		&& host
+			.strip_suffix(domain)
+			.is_some_and(|prefix| prefix.ends_with('.'))
+}
+
+/// Returns whether the request path path-matches the cookie path, as per RFC 6265 section 5.1.4.
+fn path_matches(request: &str, cookie: &str) -> bool {
+	request == cookie
+		|| request
+			.strip_prefix(cookie)
+			.is_some_and(|rest| cookie.ends_with('/') || rest.starts_with('/'))
+}
+
+/// Computes the default cookie path from the request path, as per RFC 6265 section 5.1.4.
+fn default_path(path: &str) -> String {
+	match path.rfind('/') {
+		Some(0) | None => "/".to_string(),
+		Some(index) => path[..index].to_string(),
+	}
+}
@@ -0,0 +1,418 @@
+mod jar;
+#[cfg(feature = "secure-cookies")]
+mod secure;
+
+pub use jar::*;
+#[cfg(feature = "secure-cookies")]
+pub use secure::*;
+
+use std::{
+	fmt,
+	time::{Duration, SystemTime},
+};
+
+use crate::{header, Error, Header, IntoHeader};
+
+/// The longest lifetime a cookie can be given with `Max-Age`, as recommended by RFC 6265bis.
+const MAX_AGE_LIMIT: u64 = 400 * 24 * 60 * 60;
+
+/// The `SameSite` attribute of a cookie.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SameSite {
+	Strict,
+	Lax,
+	None,
+}
+
+impl SameSite {
+	#[must_use]
+	pub fn as_str(&self) -> &str {
+		match self {
+			Self::Strict => "Strict",
+			Self::Lax => "Lax",
+			Self::None => "None",
+		}
+	}
+}
+
+/// A cookie, along with the attributes it was set with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cookie {
+	pub(crate) name: String,
+	pub(crate) value: String,
+	pub(crate) domain: Option<String>,
+	pub(crate) path: Option<String>,
+	pub(crate) expires: Option<SystemTime>,
+	pub(crate) max_age: Option<i64>,
+	pub(crate) secure: bool,
+	pub(crate) http_only: bool,
+	pub(crate) same_site: Option<SameSite>,
+}
+
+impl Cookie {
+	/// Creates a builder for a cookie to send in a `set-cookie` header.
+	///
+	/// Characters that are not allowed in the name or value, such as `;`,
+	/// are percent-encoded when the cookie is written, so that they cannot
+	/// add attributes of their own.
+	pub fn builder<N: Into<String>, V: Into<String>>(name: N, value: V) -> CookieBuilder {
+		CookieBuilder {
+			cookie: Self {
+				name: name.into(),
+				value: value.into(),
+				domain: None,
+				path: None,
+				expires: None,
+				max_age: None,
+				secure: false,
+				http_only: false,
+				same_site: None,
+			},
+		}
+	}
+
+	/// Creates a builder for a cookie that removes the cookie with the
+	/// given name from the client.
+	///
+	/// The domain and path must match the ones the cookie was set with.
+	pub fn removal<N: Into<String>>(name: N) -> CookieBuilder {
+		let mut builder = Self::builder(name, "");
+
+		builder.cookie.expires = Some(SystemTime::UNIX_EPOCH);
+		builder.cookie.max_age = Some(0);
+		builder
+	}
+
+	/// Parses the value of a `set-cookie` header.
+	///
+	/// Unknown attributes and attributes with invalid values are ignored.
+	///
+	/// # Errors
+	/// - If the cookie does not have a name-value pair.
+	pub fn parse(set_cookie: &str) -> Result<Self, Error> {
+		let mut parts = set_cookie.split(';');
+		let (name, value) = parts
+			.next()
+			.and_then(|pair| pair.split_once('='))
+			.ok_or(Error::InvalidCookie)?;
+
+		let name = name.trim();
+
+		if name.is_empty() {
+			return Err(Error::InvalidCookie);
+		}
+
+		let mut cookie = Self {
+			name: name.to_string(),
+			value: value.trim().to_string(),
+			domain: None,
+			path: None,
+			expires: None,
+			max_age: None,
+			secure: false,
+			http_only: false,
+			same_site: None,
+		};
+
+		for attribute in parts {
+			let (key, value) = attribute
+				.split_once('=')
+				.map_or((attribute, ""), |(key, value)| (key, value));
+			let key = key.trim();
+			let value = value.trim();
+
+			if key.eq_ignore_ascii_case("domain") {
+				// a leading dot is ignored, and the domain is always lowercase
+				let domain = value.trim_start_matches('.').to_ascii_lowercase();
+
+				if !domain.is_empty() {
+					cookie.domain = Some(domain);
+				}
+			} else if key.eq_ignore_ascii_case("path") {
+				if value.starts_with('/') {
+					cookie.path = Some(value.to_string());
+				}
+			} else if key.eq_ignore_ascii_case("expires") {
+				if let Ok(expires) = httpdate::parse_http_date(value) {
+					cookie.expires = Some(expires);
+				}
+			} else if key.eq_ignore_ascii_case("max-age") {
+				if let Ok(max_age) = value.parse() {
+					cookie.max_age = Some(max_age);
+				}
+			} else if key.eq_ignore_ascii_case("secure") {
+				cookie.secure = true;
+			} else if key.eq_ignore_ascii_case("httponly") {
+				cookie.http_only = true;
+			} else if key.eq_ignore_ascii_case("samesite") {
+				cookie.same_site = if value.eq_ignore_ascii_case("strict") {
+					Some(SameSite::Strict)
+				} else if value.eq_ignore_ascii_case("lax") {
+					Some(SameSite::Lax)
+				} else if value.eq_ignore_ascii_case("none") {
+					Some(SameSite::None)
+				} else {
+					None
+				};
+			}
+		}
+
+		Ok(cookie)
+	}
+
+	#[must_use]
+	pub fn name(&self) -> &str {
+		&self.name
+	}
+
+	#[must_use]
+	pub fn value(&self) -> &str {
+		&self.value
+	}
+
+	#[must_use]
+	pub fn domain(&self) -> Option<&str> {
+		self.domain.as_deref()
+	}
+
+	#[must_use]
+	pub fn path(&self) -> Option<&str> {
+		self.path.as_deref()
+	}
+
+	#[must_use]
+	pub fn expires(&self) -> Option<SystemTime> {
+		self.expires
+	}
+
+	#[must_use]
+	pub fn max_age(&self) -> Option<i64> {
+		self.max_age
+	}
+
+	#[must_use]
+	pub fn secure(&self) -> bool {
+		self.secure
+	}
+
+	#[must_use]
+	pub fn http_only(&self) -> bool {
+		self.http_only
+	}
+
+	#[must_use]
+	pub fn same_site(&self) -> Option<SameSite> {
+		self.same_site
+	}
+
+	/// Returns the time at which the cookie expires, relative to `now`.
+	///
+	/// `Max-Age` takes precedence over `Expires`, and `None` is returned
+	/// for session cookies.
+	pub(crate) fn expiry(&self, now: SystemTime) -> Option<SystemTime> {
+		match self.max_age {
+			Some(max_age) if max_age <= 0 => Some(SystemTime::UNIX_EPOCH),
+			Some(max_age) => {
+				Some(now + Duration::from_secs(max_age.unsigned_abs().min(MAX_AGE_LIMIT)))
+			}
+			None => self.expires,
+		}
+	}
+}
+
+impl fmt::Display for Cookie {
+	/// Formats the cookie as the value of a `set-cookie` header.
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write_encoded(f, &self.name, header::is_token_char)?;
+		write!(f, "=")?;
+		write_encoded(f, &self.value, is_cookie_octet)?;
+
+		if let Some(domain) = &self.domain {
+			write!(f, "; Domain=")?;
+			write_encoded(f, domain, is_attribute_value)?;
+		}
+
+		if let Some(path) = &self.path {
+			write!(f, "; Path=")?;
+			write_encoded(f, path, is_attribute_value)?;
+		}
+
+		if let Some(expires) = self.expires {
+			write!(f, "; Expires={}", httpdate::fmt_http_date(expires))?;
+		}
+
+		if let Some(max_age) = self.max_age {
+			write!(f, "; Max-Age={max_age}")?;
+		}
+
+		if self.secure {
+			write!(f, "; Secure")?;
+		}
+
+		if self.http_only {
+			write!(f, "; HttpOnly")?;
+		}
+
+		if let Some(same_site) = self.same_site {
+			write!(f, "; SameSite={}", same_site.as_str())?;
+		}
+
+		Ok(())
+	}
+}
+
+/// Writes the string, percent-encoding every byte that is not allowed.
+fn write_encoded(f: &mut fmt::Formatter<'_>, value: &str, allowed: fn(u8) -> bool) -> fmt::Result {
+	for b in value.bytes() {
+		if allowed(b) {
+			write!(f, "{}", char::from(b))?;
+		} else {
+			write!(f, "%{b:02X}")?;
+		}
+	}
+
+	Ok(())
+}
+
+/// Returns `true` for an RFC 6265 cookie-octet, which excludes spaces,
+/// double quotes, commas, semicolons and backslashes.
+fn is_cookie_octet(b: u8) -> bool {
+	matches!(b, 0x21 | 0x23..=0x2b | 0x2d..=0x3a | 0x3c..=0x5b | 0x5d..=0x7e)
+}
+
+/// Returns `true` for a character allowed in the value of an attribute,
+/// which is anything but control characters and semicolons.
+fn is_attribute_value(b: u8) -> bool {
+	(0x20..=0x7e).contains(&b) && b != b';'
+}
+
+impl<'a> IntoHeader<'a> for Cookie {
+	fn into_header(self) -> Header<'a> {
+		(header::SET_COOKIE, self.to_string()).into_header()
+	}
+}
+
+/// A builder for a [`Cookie`] to send in a `set-cookie` header.
+#[allow(clippy::module_name_repetitions)]
+#[must_use]
+#[derive(Debug, Clone)]
+pub struct CookieBuilder {
+	cookie: Cookie,
+}
+
+impl CookieBuilder {
+	pub fn domain<D: Into<String>>(mut self, domain: D) -> Self {
+		self.cookie.domain = Some(domain.into());
+		self
+	}
+
+	pub fn path<P: Into<String>>(mut self, path: P) -> Self {
+		self.cookie.path = Some(path.into());
+		self
+	}
+
+	pub fn expires(mut self, expires: SystemTime) -> Self {
+		self.cookie.expires = Some(expires);
+		self
+	}
+
+	pub fn max_age(mut self, max_age: Duration) -> Self {
+		self.cookie.max_age = Some(i64::try_from(max_age.as_secs()).unwrap_or(i64::MAX));
+		self
+	}
+
+	pub fn secure(mut self, secure: bool) -> Self {
+		self.cookie.secure = secure;
+		self
+	}
+
+	pub fn http_only(mut self, http_only: bool) -> Self {
+		self.cookie.http_only = http_only;
+		self
+	}
+
+	pub fn same_site(mut self, same_site: SameSite) -> Self {
+		self.cookie.same_site = Some(same_site);
+		self
+	}
+
+	#[must_use]
+	pub fn build(self) -> Cookie {
+		self.cookie
+	}
+}
+
+impl<'a> IntoHeader<'a> for CookieBuilder {
+	fn into_header(self) -> Header<'a> {
+		self.build().into_header()
+	}
+}
+
+/// The cookies sent by a client in the `cookie` header of a request.
+#[derive(Debug, Clone, Default)]
+pub struct Cookies<'r> {
+	pairs: Vec<(&'r str, &'r str)>,
+}
+
+impl<'r> Cookies<'r> {
+	/// Parses the value of a `cookie` header.
+	///
+	/// Pairs without a `=` are ignored, and values wrapped in double quotes are unquoted.
+	#[must_use]
+	pub fn parse(header: &'r str) -> Self {
+		let pairs = header
+			.split(';')
+			.filter_map(|pair| pair.split_once('='))
+			.map(|(name, value)| {
+				let value = value.trim();
+				let value = value
+					.strip_prefix('"')
+					.and_then(|v| v.strip_suffix('"'))
+					.unwrap_or(value);
+
+				(name.trim(), value)
+			})
+			.filter(|(name, _)| !name.is_empty())
+			.collect();
+
+		Self { pairs }
+	}
+
+	/// Returns the value of the first cookie with the given name.
+	#[must_use]
+	pub fn get(&self, name: &str) -> Option<&'r str> {
+		self.pairs
+			.iter()
+			.find(|(n, _)| *n == name)
+			.map(|(_, value)| *value)
+	}
+
+	/// Returns the verified value of a cookie signed with [`Key::sign`].
+	#[cfg(feature = "secure-cookies")]
+	#[must_use]
+	pub fn get_signed(&self, key: &Key, name: &str) -> Option<String> {
+		key.verify(name, self.get(name)?)
+	}
+
+	/// Returns the decrypted value of a cookie encrypted with [`Key::encrypt`].
+	#[cfg(feature = "secure-cookies")]
+	#[must_use]
+	pub fn get_private(&self, key: &Key, name: &str) -> Option<String> {
+		key.decrypt(name, self.get(name)?)
+	}
+
+	/// Returns an iterator over the name-value pairs, in the order they were sent.
+	pub fn iter(&self) -> impl Iterator<Item = (&'r str, &'r str)> + '_ {
+		self.pairs.iter().copied()
+	}
+
+	#[must_use]
+	pub fn is_empty(&self) -> bool {
+		self.pairs.is_empty()
+	}
+
+	#[must_use]
+	pub fn len(&self) -> usize {
+		self.pairs.len()
+	}
+}
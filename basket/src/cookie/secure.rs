@@ -0,0 +1,171 @@
+use std::fmt;
+
+use aes_gcm::{
+	aead::{rand_core::RngCore, Aead, AeadCore, KeyInit, OsRng, Payload},
+	Aes256Gcm, Nonce,
+};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use crate::Error;
+
+use super::Cookie;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// The minimum length of the secret a [`Key`] is derived from.
+pub const MIN_SECRET_LEN: usize = 32;
+
+/// The length of a base64-encoded HMAC-SHA256 signature.
+const SIGNATURE_LEN: usize = 43;
+const NONCE_LEN: usize = 12;
+
+/// A server secret used to sign and encrypt cookies.
+///
+/// Separate signing and encryption keys are derived from the secret, so the
+/// same secret can safely be used for both signed and private cookies.
+#[derive(Clone)]
+pub struct Key {
+	signing: [u8; 32],
+	encryption: [u8; 32],
+}
+
+impl fmt::Debug for Key {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("Key").finish_non_exhaustive()
+	}
+}
+
+impl Key {
+	/// Derives a key from a secret.
+	///
+	/// # Errors
+	/// - If the secret is shorter than [`MIN_SECRET_LEN`] bytes.
+	pub fn new(secret: &[u8]) -> Result<Self, Error> {
+		if secret.len() < MIN_SECRET_LEN {
+			return Err(Error::InvalidKey);
+		}
+
+		Ok(Self {
+			signing: derive(secret, b"basket-signed-cookies"),
+			encryption: derive(secret, b"basket-private-cookies"),
+		})
+	}
+
+	/// Generates a key from a random secret.
+	///
+	/// Cookies signed or encrypted with a generated key cannot be read
+	/// after the server restarts.
+	#[must_use]
+	pub fn generate() -> Self {
+		let mut secret = [0; MIN_SECRET_LEN];
+
+		OsRng.fill_bytes(&mut secret);
+
+		Self {
+			signing: derive(&secret, b"basket-signed-cookies"),
+			encryption: derive(&secret, b"basket-private-cookies"),
+		}
+	}
+
+	/// Signs the value of the cookie, so that it can be read by the client
+	/// but not tampered with.
+	#[must_use]
+	pub fn sign(&self, mut cookie: Cookie) -> Cookie {
+		let signature = self.signature(&cookie.name, &cookie.value).finalize();
+
+		cookie.value = format!(
+			"{}{}",
+			URL_SAFE_NO_PAD.encode(signature.into_bytes()),
+			cookie.value
+		);
+		cookie
+	}
+
+	/// Verifies a signed cookie value and returns the original value.
+	#[must_use]
+	pub fn verify(&self, name: &str, value: &str) -> Option<String> {
+		if !value.is_char_boundary(SIGNATURE_LEN) {
+			return None;
+		}
+
+		let (signature, value) = value.split_at(SIGNATURE_LEN);
+		let signature = URL_SAFE_NO_PAD.decode(signature).ok()?;
+
+		self.signature(name, value)
+			.verify_slice(&signature)
+			.ok()
+			.map(|()| value.to_string())
+	}
+
+	/// Encrypts the value of the cookie, so that it can neither be read
+	/// nor tampered with by the client.
+	#[must_use]
+	pub fn encrypt(&self, mut cookie: Cookie) -> Cookie {
+		let cipher = Aes256Gcm::new(&self.encryption.into());
+		let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+		let payload = Payload {
+			msg: cookie.value.as_bytes(),
+			aad: cookie.name.as_bytes(),
+		};
+
+		// encryption only fails if the payload is larger than the cipher allows,
+		// which a cookie can never be
+		let Ok(ciphertext) = cipher.encrypt(&nonce, payload) else {
+			unreachable!("cookie value is too large to encrypt");
+		};
+
+		let mut sealed = nonce.to_vec();
+
+		sealed.extend(ciphertext);
+		cookie.value = URL_SAFE_NO_PAD.encode(sealed);
+		cookie
+	}
+
+	/// Decrypts a private cookie value and returns the original value.
+	#[must_use]
+	pub fn decrypt(&self, name: &str, value: &str) -> Option<String> {
+		let sealed = URL_SAFE_NO_PAD.decode(value).ok()?;
+
+		if sealed.len() < NONCE_LEN {
+			return None;
+		}
+
+		let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+		let cipher = Aes256Gcm::new(&self.encryption.into());
+		let payload = Payload {
+			msg: ciphertext,
+			aad: name.as_bytes(),
+		};
+
+		let plaintext = cipher.decrypt(Nonce::from_slice(nonce), payload).ok()?;
+
+		String::from_utf8(plaintext).ok()
+	}
+
+	fn signature(&self, name: &str, value: &str) -> HmacSha256 {
+		let mut mac = hmac(&self.signing);
+
+		// the name is included so that a signed value cannot be moved to another cookie
+		mac.update(name.as_bytes());
+		mac.update(b"=");
+		mac.update(value.as_bytes());
+		mac
+	}
+}
+
+fn hmac(key: &[u8]) -> HmacSha256 {
+	let Ok(mac) = <HmacSha256 as Mac>::new_from_slice(key) else {
+		unreachable!("hmac accepts keys of any length");
+	};
+
+	mac
+}
+
+fn derive(secret: &[u8], label: &[u8]) -> [u8; 32] {
+	let mut mac = hmac(secret);
+
+	mac.update(label);
+	mac.finalize().into_bytes().into()
+}
@@ -1,45 +1,166 @@
 use core::fmt;
-use std::{io, num::ParseIntError, str::Utf8Error, string::FromUtf8Error};
+use std::{borrow::Cow, io, num::ParseIntError, str::Utf8Error, string::FromUtf8Error};
 
 use url::ParseError;
 
+use crate::{proxy, Response};
+
+/// The maximum number of bytes of the body shown when an [`Error::Status`] is displayed.
+const SNIPPET_LEN: usize = 256;
+
 #[derive(Debug)]
 pub enum Error {
+	BodyTooLarge,
 	ExpectedBody,
+	HeaderTooLarge,
+	HeadersTooLarge,
 	Io(io::Error),
+	#[cfg(feature = "cookies")]
+	InvalidCookie,
 	InvalidFormat,
+	InvalidHeader(String),
 	InvalidInt(ParseIntError),
+	#[cfg(feature = "secure-cookies")]
+	InvalidKey,
+	#[cfg(feature = "multipart")]
+	InvalidMultipart,
+	InvalidStatus(u16),
 	InvalidUrl(ParseError),
 	InvalidUtf8(Utf8Error),
 	#[cfg(feature = "json")]
 	Json(serde_json::Error),
 	#[cfg(feature = "xml")]
 	Xml(quick_xml::DeError),
+	#[cfg(feature = "form")]
+	UrlEncoded(String),
+	#[cfg(feature = "multipart")]
+	PartTooLarge,
+	#[cfg(feature = "multipart")]
+	MultipartTooLarge,
+	/// An `https` URL was tunnelled through a proxy by a connector that does
+	/// not wrap the tunnel in TLS, which would send the request in plaintext.
+	PlaintextTunnel,
+	/// A SOCKS proxy could not connect to the destination, with its reply code.
+	Socks(u8),
+	/// A SOCKS proxy rejected the credentials, or required ones that were not set.
+	SocksAuth,
+	StartLineTooLong,
+	/// A response had a status other than 2xx, from [`Response::error_for_status`](crate::Response::error_for_status).
+	///
+	/// The whole response is kept, rather than only its status, URL and the
+	/// start of its body, so that its headers and full body can be read too.
+	/// The status, URL and start of the body are shown when it is displayed.
+	Status(Box<Response<'static>>),
+	/// A request with a custom [`Connector`](crate::Connector) was sent
+	/// without blocking, which only connects over TCP or a Unix socket.
+	UnsupportedConnector,
 	UnsupportedHttp,
+	/// A proxy URL had a scheme other than `http`, `socks5` or `socks5h`.
+	UnsupportedProxy(String),
 	UnknownMethod,
+	TooManyHeaders,
 	TooManyRedirects,
 }
 
 impl fmt::Display for Error {
 	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
 		match self {
+			Error::BodyTooLarge => write!(f, "body exceeds the size limit"),
 			Error::ExpectedBody => write!(f, "expected body"),
+			Error::HeaderTooLarge => write!(f, "header exceeds the size limit"),
+			Error::HeadersTooLarge => write!(f, "headers exceed the size limit"),
 			Error::Io(e) => write!(f, "io error: {e}"),
+			#[cfg(feature = "cookies")]
+			Error::InvalidCookie => write!(f, "invalid cookie"),
 			Error::InvalidFormat => write!(f, "invalid format when parsing resposne"),
+			Error::InvalidHeader(name) => write!(f, "invalid header: {name:?}"),
 			Error::InvalidInt(e) => write!(f, "invalid int: {e}"),
+			#[cfg(feature = "secure-cookies")]
+			Error::InvalidKey => write!(f, "cookie key must be at least 32 bytes"),
+			#[cfg(feature = "multipart")]
+			Error::InvalidMultipart => write!(f, "invalid multipart body"),
+			Error::InvalidStatus(code) => write!(f, "invalid status code: {code}"),
 			Error::InvalidUrl(e) => write!(f, "invalid url: {e}"),
 			Error::InvalidUtf8(e) => write!(f, "invalid utf8: {e}"),
 			#[cfg(feature = "json")]
 			Error::Json(e) => write!(f, "json error: {e}"),
 			#[cfg(feature = "xml")]
 			Error::Xml(e) => write!(f, "xml error: {e}"),
-			Error::UnsupportedHttp => write!(f, "only HTTP/1.1 is supported"),
+			#[cfg(feature = "form")]
+			Error::UrlEncoded(e) => write!(f, "urlencoded error: {e}"),
+			#[cfg(feature = "multipart")]
+			Error::PartTooLarge => write!(f, "multipart part exceeds the size limit"),
+			#[cfg(feature = "multipart")]
+			Error::MultipartTooLarge => write!(f, "multipart body exceeds the size limit"),
+			Error::PlaintextTunnel => {
+				write!(
+					f,
+					"an https tunnel through a proxy needs a connector that starts tls"
+				)
+			}
+			Error::Socks(code) => write!(f, "socks proxy error: {}", proxy::reply_message(*code)),
+			Error::SocksAuth => write!(f, "socks proxy authentication failed"),
+			Error::StartLineTooLong => write!(f, "request or status line exceeds the size limit"),
+			Error::Status(response) => {
+				write!(f, "status {}", response.status())?;
+
+				if let Some(url) = response.url() {
+					write!(f, " for {url}")?;
+				}
+
+				let snippet = snippet(response.body.as_deref().unwrap_or_default());
+
+				if !snippet.is_empty() {
+					write!(f, ": {snippet}")?;
+				}
+
+				Ok(())
+			}
+			Error::UnsupportedConnector => {
+				write!(
+					f,
+					"a custom connector can only be used for blocking requests"
+				)
+			}
+			Error::UnsupportedHttp => write!(f, "only HTTP/1.0 and HTTP/1.1 are supported"),
+			Error::UnsupportedProxy(scheme) => write!(f, "unsupported proxy scheme: {scheme}"),
 			Error::UnknownMethod => write!(f, "unknown method"),
+			Error::TooManyHeaders => write!(f, "too many headers"),
 			Error::TooManyRedirects => write!(f, "too many redirects"),
 		}
 	}
 }
 
+impl std::error::Error for Error {
+	fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+		match self {
+			Error::Io(e) => Some(e),
+			Error::InvalidInt(e) => Some(e),
+			Error::InvalidUrl(e) => Some(e),
+			Error::InvalidUtf8(e) => Some(e),
+			#[cfg(feature = "json")]
+			Error::Json(e) => Some(e),
+			#[cfg(feature = "xml")]
+			Error::Xml(e) => Some(e),
+			_ => None,
+		}
+	}
+}
+
+#[cfg(feature = "form")]
+impl serde::ser::Error for Error {
+	fn custom<T: fmt::Display>(msg: T) -> Self {
+		Self::UrlEncoded(msg.to_string())
+	}
+}
+
+#[cfg(feature = "form")]
+impl serde::de::Error for Error {
+	fn custom<T: fmt::Display>(msg: T) -> Self {
+		Self::UrlEncoded(msg.to_string())
+	}
+}
+
 #[cfg(feature = "xml")]
 impl From<quick_xml::DeError> for Error {
 	fn from(value: quick_xml::DeError) -> Self {
@@ -83,3 +204,16 @@ impl From<ParseError> for Error {
 		Self::InvalidUrl(value)
 	}
 }
+
+/// Returns the start of a body, for diagnosing an error.
+fn snippet(body: &[u8]) -> Cow<'_, str> {
+	let mut end = body.len().min(SNIPPET_LEN);
+
+	// a multi-byte character cut off by the limit is dropped rather than replaced,
+	// by moving back past any continuation bytes (0b10xxxxxx)
+	while end > 0 && end < body.len() && body[end] & 0xc0 == 0x80 {
+		end -= 1;
+	}
+
+	String::from_utf8_lossy(&body[..end])
+}
@@ -0,0 +1,56 @@
+use std::{
+	any::{Any, TypeId},
+	collections::HashMap,
+	fmt,
+};
+
+/// A type map of values attached to a request, such as the session added by
+/// [`SessionLayer`](crate::server::SessionLayer).
+#[derive(Default)]
+pub struct Extensions {
+	map: HashMap<TypeId, Box<dyn Any + Send + Sync>>,
+}
+
+impl fmt::Debug for Extensions {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("Extensions")
+			.field("len", &self.map.len())
+			.finish()
+	}
+}
+
+impl Extensions {
+	#[must_use]
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Inserts a value, returning the previous value of the same type.
+	pub fn insert<T: Any + Send + Sync>(&mut self, value: T) -> Option<T> {
+		self.map
+			.insert(TypeId::of::<T>(), Box::new(value))
+			.and_then(|previous| previous.downcast().ok())
+			.map(|previous| *previous)
+	}
+
+	#[must_use]
+	pub fn get<T: Any + Send + Sync>(&self) -> Option<&T> {
+		self.map
+			.get(&TypeId::of::<T>())
+			.and_then(|value| value.downcast_ref())
+	}
+
+	#[must_use]
+	pub fn get_mut<T: Any + Send + Sync>(&mut self) -> Option<&mut T> {
+		self.map
+			.get_mut(&TypeId::of::<T>())
+			.and_then(|value| value.downcast_mut())
+	}
+
+	pub fn remove<T: Any + Send + Sync>(&mut self) -> Option<T> {
+		self.map
+			.remove(&TypeId::of::<T>())
+			.and_then(|value| value.downcast().ok())
+			.map(|value| *value)
+	}
+}
@@ -2,22 +2,11 @@ use std::io::Read;
 
 use crate::Error;
 
-pub fn skip<R, const N: usize>(reader: &mut R, seq: &[u8; N]) -> Result<(), Error>
-where
-	R: Read,
-{
-	let mut buf = [0; N];
-
-	reader.read_exact(&mut buf)?;
-
-	if &buf != seq {
-		return Err(Error::InvalidFormat);
-	}
-
-	Ok(())
-}
-
-pub fn until<R>(reader: &mut R, seq: &[u8]) -> Result<Vec<u8>, Error>
+/// Reads until the sequence is found, returning the bytes before it.
+///
+/// Returns `too_long` if more than `limit` bytes are read without finding
+/// the sequence, so a peer cannot make the buffer grow without bound.
+pub fn until<R>(reader: &mut R, seq: &[u8], limit: usize, too_long: Error) -> Result<Vec<u8>, Error>
 where
 	R: Read,
 {
@@ -29,7 +18,14 @@ where
 	while !extracted[i..].starts_with(seq) {
 		i += 1;
 
-		reader.take(1).read_to_end(&mut extracted)?;
+		if i > limit {
+			return Err(too_long);
+		}
+
+		// the sequence can never be found once the reader is exhausted
+		if reader.take(1).read_to_end(&mut extracted)? == 0 {
+			return Err(Error::InvalidFormat);
+		}
 	}
 
 	// once it starts with seq, we need to remove it
@@ -37,18 +33,3 @@ where
 
 	Ok(extracted)
 }
-
-pub fn http_version<R>(reader: &mut R) -> Result<(), Error>
-where
-	R: Read,
-{
-	let mut buf = [0; b"HTTP/1.1".len()];
-
-	reader.read_exact(&mut buf)?;
-
-	if &buf != b"HTTP/1.1" {
-		return Err(Error::UnsupportedHttp);
-	}
-
-	Ok(())
-}
@@ -0,0 +1,180 @@
+use super::{Header, HeaderValue, IntoHeader, TypedHeader};
+use crate::Error;
+
+/// A collection of headers with case-insensitive names.
+///
+/// Every value of a name is kept in insertion order, and headers are written
+/// in the order they were first added.
+#[derive(Debug, Default)]
+pub struct HeaderMap<'h> {
+	headers: Vec<Header<'h>>,
+}
+
+impl<'h> HeaderMap<'h> {
+	#[must_use]
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	#[must_use]
+	pub fn len(&self) -> usize {
+		self.headers.len()
+	}
+
+	#[must_use]
+	pub fn is_empty(&self) -> bool {
+		self.headers.is_empty()
+	}
+
+	#[must_use]
+	pub fn contains(&self, name: &str) -> bool {
+		self.get(name).is_some()
+	}
+
+	/// Returns the first value of the header, if it is valid UTF-8.
+	#[must_use]
+	pub fn get(&self, name: &str) -> Option<&str> {
+		self.get_value(name).and_then(HeaderValue::to_str)
+	}
+
+	/// Returns the first value of the header, which may not be valid UTF-8.
+	#[must_use]
+	pub fn get_value(&self, name: &str) -> Option<&HeaderValue<'h>> {
+		self.headers
+			.iter()
+			.find(|header| header.name.eq_ignore_ascii_case(name))
+			.map(|header| &header.value)
+	}
+
+	/// Returns every value of the header that is valid UTF-8, in insertion order.
+	pub fn get_all<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+		self.headers
+			.iter()
+			.filter(move |header| header.name.eq_ignore_ascii_case(name))
+			.filter_map(|header| header.value.to_str())
+	}
+
+	/// Parses the header as a [`TypedHeader`], returning `None` if it is
+	/// missing or invalid.
+	///
+	/// The values of a list header sent multiple times are combined, while
+	/// any other header only uses its first value.
+	#[must_use]
+	pub fn typed_get<H: TypedHeader>(&self) -> Option<H> {
+		let mut values = self.get_all(H::NAME);
+		let first = values.next()?;
+
+		if !H::LIST {
+			return H::decode(first);
+		}
+
+		let rest = values.collect::<Vec<_>>();
+
+		if rest.is_empty() {
+			H::decode(first)
+		} else {
+			H::decode(&format!("{first}, {}", rest.join(", ")))
+		}
+	}
+
+	/// Sets the header, replacing every existing value of the same name and
+	/// returning the first of them.
+	///
+	/// The header keeps the position of the value it replaced.
+	pub fn insert<H: IntoHeader<'h>>(&mut self, header: H) -> Option<HeaderValue<'h>> {
+		let header = header.into_header();
+		let Some(index) = self.position(&header.name) else {
+			self.headers.push(header);
+
+			return None;
+		};
+
+		let previous = std::mem::replace(&mut self.headers[index], header);
+
+		// remove any later values with the same name
+		let mut i = 0;
+
+		self.headers.retain(|h| {
+			i += 1;
+			i <= index + 1 || !h.name.eq_ignore_ascii_case(&previous.name)
+		});
+
+		Some(previous.value)
+	}
+
+	/// Adds a value for the header, keeping any existing values.
+	pub fn append<H: IntoHeader<'h>>(&mut self, header: H) {
+		self.headers.push(header.into_header());
+	}
+
+	/// Removes every value of the header, returning the first of them.
+	pub fn remove(&mut self, name: &str) -> Option<HeaderValue<'h>> {
+		let index = self.position(name)?;
+		let previous = self.headers.remove(index);
+
+		self.headers
+			.retain(|header| !header.name.eq_ignore_ascii_case(name));
+
+		Some(previous.value)
+	}
+
+	pub fn iter(&self) -> std::slice::Iter<'_, Header<'h>> {
+		self.headers.iter()
+	}
+
+	/// Checks every header with [`Header::validate`], so that a message can be
+	/// rejected before any of it is written.
+	pub(crate) fn validate(&self) -> Result<(), Error> {
+		self.headers.iter().try_for_each(Header::validate)
+	}
+
+	/// Copies any borrowed names and values, so the map no longer borrows
+	/// from the buffer it was parsed from.
+	#[must_use]
+	pub fn into_owned(self) -> HeaderMap<'static> {
+		HeaderMap {
+			headers: self.headers.into_iter().map(Header::into_owned).collect(),
+		}
+	}
+
+	fn position(&self, name: &str) -> Option<usize> {
+		self.headers
+			.iter()
+			.position(|header| header.name.eq_ignore_ascii_case(name))
+	}
+}
+
+impl<'a, 'h> IntoIterator for &'a HeaderMap<'h> {
+	type Item = &'a Header<'h>;
+	type IntoIter = std::slice::Iter<'a, Header<'h>>;
+
+	fn into_iter(self) -> Self::IntoIter {
+		self.iter()
+	}
+}
+
+impl<'h> IntoIterator for HeaderMap<'h> {
+	type Item = Header<'h>;
+	type IntoIter = std::vec::IntoIter<Header<'h>>;
+
+	fn into_iter(self) -> Self::IntoIter {
+		self.headers.into_iter()
+	}
+}
+
+impl<'h, H: IntoHeader<'h>> FromIterator<H> for HeaderMap<'h> {
+	fn from_iter<I: IntoIterator<Item = H>>(iter: I) -> Self {
+		let mut map = Self::new();
+
+		map.extend(iter);
+		map
+	}
+}
+
+impl<'h, H: IntoHeader<'h>> Extend<H> for HeaderMap<'h> {
+	fn extend<I: IntoIterator<Item = H>>(&mut self, iter: I) {
+		for header in iter {
+			self.append(header);
+		}
+	}
+}
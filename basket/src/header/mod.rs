@@ -0,0 +1,354 @@
+mod map;
+mod typed;
+mod value;
+
+pub use map::HeaderMap;
+pub use typed::*;
+pub use value::HeaderValue;
+
+use std::{borrow::Cow, io::Read};
+
+use crate::{extract, Error, ParseConfig};
+
+#[derive(Debug)]
+pub struct Header<'a> {
+	pub name: Cow<'a, str>,
+	pub value: HeaderValue<'a>,
+}
+
+impl Header<'_> {
+	/// Checks that the name is a token and the value only contains
+	/// characters allowed in a field value, so the header cannot inject
+	/// other headers or split the message.
+	///
+	/// # Errors
+	/// - If the name or value contains characters that are not allowed.
+	pub fn validate(&self) -> Result<(), Error> {
+		if is_valid_name(&self.name) && is_valid_value(self.value.as_bytes()) {
+			Ok(())
+		} else {
+			Err(Error::InvalidHeader(self.name.to_string()))
+		}
+	}
+
+	#[must_use]
+	pub fn into_owned(self) -> Header<'static> {
+		Header {
+			name: Cow::Owned(self.name.into_owned()),
+			value: self.value.into_owned(),
+		}
+	}
+}
+
+/// Returns `true` if the name is an RFC 9110 token.
+#[must_use]
+pub fn is_valid_name(name: &str) -> bool {
+	!name.is_empty() && name.bytes().all(is_token_char)
+}
+
+/// Returns `true` if the byte can be part of an RFC 9110 token.
+pub(crate) fn is_token_char(b: u8) -> bool {
+	b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&b)
+}
+
+/// Returns `true` if the value only contains visible characters, spaces,
+/// tabs and non-ASCII characters, which excludes `\r` and `\n`.
+#[must_use]
+pub fn is_valid_value(value: &[u8]) -> bool {
+	value
+		.iter()
+		.all(|&b| b == b'\t' || b == b' ' || (0x21..=0x7e).contains(&b) || b >= 0x80)
+}
+
+/// Returns `true` if the value is an RFC 9112 content length, which is only
+/// digits, so that a sign such as `+5` is not read as a length.
+pub(crate) fn is_valid_length(value: &str) -> bool {
+	!value.is_empty() && value.bytes().all(|b| b.is_ascii_digit())
+}
+
+pub const ACCEPT: &str = "accept";
+pub const ACCEPT_ENCODING: &str = "accept-encoding";
+pub const AUTHORIZATION: &str = "authorization";
+pub const CACHE_CONTROL: &str = "cache-control";
+pub const CONTENT_ENCODING: &str = "content-encoding";
+pub const CONTENT_TYPE: &str = "content-type";
+pub const CONTENT_LENGTH: &str = "content-length";
+pub const CONNECTION: &str = "connection";
+pub const COOKIE: &str = "cookie";
+pub const DATE: &str = "date";
+pub const ETAG: &str = "etag";
+pub const HOST: &str = "host";
+pub const IF_NONE_MATCH: &str = "if-none-match";
+pub const LAST_MODIFIED: &str = "last-modified";
+pub const LOCATION: &str = "location";
+pub const PROXY_AUTHORIZATION: &str = "proxy-authorization";
+pub const RETRY_AFTER: &str = "retry-after";
+pub const SET_COOKIE: &str = "set-cookie";
+pub const TRANSFER_ENCODING: &str = "transfer-encoding";
+pub const USER_AGENT: &str = "user-agent";
+pub const VARY: &str = "vary";
+
+pub const CONTENT_TYPE_JSON: Header<'static> = Header {
+	name: Cow::Borrowed(CONTENT_TYPE),
+	value: HeaderValue::from_static("application/json"),
+};
+
+pub const CONTENT_TYPE_XML: Header<'static> = Header {
+	name: Cow::Borrowed(CONTENT_TYPE),
+	value: HeaderValue::from_static("application/xml"),
+};
+
+pub const CONTENT_TYPE_FORM: Header<'static> = Header {
+	name: Cow::Borrowed(CONTENT_TYPE),
+	value: HeaderValue::from_static("application/x-www-form-urlencoded"),
+};
+
+pub const CONTENT_TYPE_PLAIN: Header<'static> = Header {
+	name: Cow::Borrowed(CONTENT_TYPE),
+	value: HeaderValue::from_static("text/plain"),
+};
+
+/// Parses the header section and removes the trailing \r\n, following
+/// the field syntax of RFC 9112.
+///
+/// # Errors
+/// - If a header name is not a token, or is followed by whitespace.
+/// - If a header value contains control characters.
+/// - If a header is folded and [`ParseConfig::obs_fold`] is disabled.
+/// - If the content length is not a valid integer, or is sent with conflicting values.
+/// - If the headers exceed any of the limits in the [`ParseConfig`].
+pub fn from_reader<R>(
+	reader: &mut R,
+	config: &ParseConfig,
+) -> Result<(HeaderMap<'static>, Option<usize>), Error>
+where
+	R: Read,
+{
+	let mut fields = FieldParser::new(config);
+
+	loop {
+		let line = extract::until(
+			reader,
+			b"\r\n",
+			config.max_header_size,
+			Error::HeaderTooLarge,
+		)?;
+
+		if line.is_empty() {
+			break;
+		}
+
+		fields.line(Cow::Owned(line))?;
+	}
+
+	fields.finish()
+}
+
+/// Parses header lines one at a time, enforcing the limits of a [`ParseConfig`].
+///
+/// Names and values borrow from lines that are borrowed, so a head parsed
+/// from a single buffer needs no allocation per header.
+pub(crate) struct FieldParser<'c, 'b> {
+	config: &'c ParseConfig,
+	headers: Vec<Header<'b>>,
+	size: usize,
+}
+
+impl<'c, 'b> FieldParser<'c, 'b> {
+	pub(crate) fn new(config: &'c ParseConfig) -> Self {
+		Self {
+			config,
+			headers: Vec::new(),
+			size: 0,
+		}
+	}
+
+	/// Parses a header line without its trailing \r\n.
+	pub(crate) fn line(&mut self, line: Cow<'b, [u8]>) -> Result<(), Error> {
+		if line.len() > self.config.max_header_size {
+			return Err(Error::HeaderTooLarge);
+		}
+
+		self.size += line.len() + 2;
+
+		if self.size > self.config.max_headers_size {
+			return Err(Error::HeadersTooLarge);
+		}
+
+		// a line starting with whitespace continues the previous value (obs-fold)
+		if matches!(line.first(), Some(b' ' | b'\t')) {
+			let Some(last) = self.headers.last_mut().filter(|_| self.config.obs_fold) else {
+				return Err(Error::InvalidFormat);
+			};
+
+			let continuation = trim_whitespace(&line);
+
+			if !is_valid_value(continuation) {
+				return Err(Error::InvalidFormat);
+			}
+
+			let mut value = last.value.as_bytes().to_vec();
+
+			value.push(b' ');
+			value.extend_from_slice(continuation);
+			last.value = value.into();
+
+			return Ok(());
+		}
+
+		if self.headers.len() >= self.config.max_headers {
+			return Err(Error::TooManyHeaders);
+		}
+
+		let colon = line
+			.iter()
+			.position(|&b| b == b':')
+			.ok_or(Error::InvalidFormat)?;
+
+		// whitespace between the name and colon is not part of the token, so it is rejected
+		if !std::str::from_utf8(&line[..colon]).is_ok_and(is_valid_name) {
+			return Err(Error::InvalidFormat);
+		}
+
+		let value = trim_whitespace(&line[colon + 1..]);
+
+		if !is_valid_value(value) {
+			return Err(Error::InvalidFormat);
+		}
+
+		let (start, end) = offsets(&line, value);
+		let (name, value) = match line {
+			Cow::Borrowed(line) => (
+				Cow::Borrowed(&line[..colon]),
+				HeaderValue::from(&line[start..end]),
+			),
+			Cow::Owned(mut line) => {
+				let value = line[start..end].to_vec();
+
+				line.truncate(colon);
+
+				(Cow::Owned(line), HeaderValue::from(value))
+			}
+		};
+
+		self.headers.push(Header {
+			name: lowercase(name),
+			value,
+		});
+
+		Ok(())
+	}
+
+	/// Returns the headers along with the content length, if one was sent.
+	pub(crate) fn finish(self) -> Result<(HeaderMap<'b>, Option<usize>), Error> {
+		let mut content_length = None;
+
+		for header in self
+			.headers
+			.iter()
+			.filter(|header| header.name == CONTENT_LENGTH)
+		{
+			let value = header.value.to_str().ok_or(Error::InvalidFormat)?;
+
+			// a list of identical lengths is allowed, since it can be caused by merging headers
+			for length in value.split(',').map(str::trim) {
+				if !is_valid_length(length) {
+					return Err(Error::InvalidFormat);
+				}
+
+				let length = length.parse()?;
+
+				if content_length.is_some_and(|previous| previous != length) {
+					return Err(Error::InvalidFormat);
+				}
+
+				content_length = Some(length);
+			}
+		}
+
+		Ok((self.headers.into_iter().collect(), content_length))
+	}
+}
+
+/// Returns the range of `part` within `whole`, which it must be a subslice of.
+fn offsets(whole: &[u8], part: &[u8]) -> (usize, usize) {
+	let start = part.as_ptr() as usize - whole.as_ptr() as usize;
+
+	(start, start + part.len())
+}
+
+/// Converts an already validated name to lowercase, only allocating if it
+/// contains uppercase characters.
+fn lowercase(name: Cow<'_, [u8]>) -> Cow<'_, str> {
+	let mut name = match name {
+		Cow::Borrowed(name) if !name.iter().any(u8::is_ascii_uppercase) => {
+			return Cow::Borrowed(std::str::from_utf8(name).unwrap_or_default());
+		}
+		Cow::Borrowed(name) => name.to_vec(),
+		Cow::Owned(name) => name,
+	};
+
+	name.make_ascii_lowercase();
+
+	// tokens are ASCII, so this cannot fail
+	Cow::Owned(String::from_utf8(name).unwrap_or_default())
+}
+
+/// Removes optional whitespace (spaces and tabs) from both ends.
+fn trim_whitespace(mut value: &[u8]) -> &[u8] {
+	while let [b' ' | b'\t', rest @ ..] = value {
+		value = rest;
+	}
+
+	while let [rest @ .., b' ' | b'\t'] = value {
+		value = rest;
+	}
+
+	value
+}
+
+#[allow(clippy::module_name_repetitions)]
+pub trait IntoHeader<'a> {
+	fn into_header(self) -> Header<'a>;
+}
+
+pub trait IntoHeaderValue<'a> {
+	fn into_header_value(self) -> Cow<'a, str>;
+}
+
+impl<'a> IntoHeader<'a> for Header<'a> {
+	fn into_header(self) -> Header<'a> {
+		self
+	}
+}
+
+impl<'a, N, V> IntoHeader<'a> for (N, V)
+where
+	N: IntoHeaderValue<'a>,
+	V: IntoHeaderValue<'a>,
+{
+	fn into_header(self) -> Header<'a> {
+		Header {
+			name: self.0.into_header_value(),
+			value: self.1.into_header_value().into(),
+		}
+	}
+}
+
+impl<'a> IntoHeaderValue<'a> for &'a str {
+	fn into_header_value(self) -> Cow<'a, str> {
+		Cow::Borrowed(self)
+	}
+}
+
+impl<'a> IntoHeaderValue<'a> for String {
+	fn into_header_value(self) -> Cow<'a, str> {
+		Cow::Owned(self)
+	}
+}
+
+impl<'a> IntoHeaderValue<'a> for usize {
+	fn into_header_value(self) -> Cow<'a, str> {
+		Cow::Owned(self.to_string())
+	}
+}
@@ -0,0 +1,814 @@
+use std::{
+	fmt::Write as _,
+	time::{Duration, SystemTime},
+};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+
+use super::{is_valid_name, Header, IntoHeader};
+
+/// A header that can be parsed from and formatted to its value.
+///
+/// Typed headers can be added with [`IntoHeader`], and read with
+/// [`HeaderMap::typed_get`](super::HeaderMap::typed_get).
+pub trait TypedHeader: Sized {
+	/// The lowercase name of the header.
+	const NAME: &'static str;
+
+	/// Whether the value is a comma-separated list, so that a header sent
+	/// multiple times can be combined into one. Otherwise, only the first
+	/// value is used.
+	const LIST: bool = false;
+
+	/// Parses the header from its value, where a list header sent multiple
+	/// times has its values joined with `, `.
+	fn decode(value: &str) -> Option<Self>;
+
+	fn encode(&self) -> String;
+}
+
+macro_rules! into_header {
+	($($ty:ty),*) => {
+		$(
+			impl<'a> IntoHeader<'a> for $ty {
+				fn into_header(self) -> Header<'a> {
+					Header {
+						name: <$ty as TypedHeader>::NAME.into(),
+						value: self.encode().into(),
+					}
+				}
+			}
+		)*
+	};
+}
+
+into_header!(
+	Accept,
+	Authorization,
+	CacheControl,
+	ContentLength,
+	ContentType,
+	Date,
+	ETag,
+	Host,
+	IfNoneMatch,
+	LastModified,
+	Location,
+	RetryAfter,
+	UserAgent
+);
+
+/// Splits a value on commas that are not inside a quoted string.
+fn split_list(value: &str) -> impl Iterator<Item = &str> {
+	split_quoted(value, ',')
+}
+
+/// Splits a value on a separator that is not inside a quoted string,
+/// skipping empty items.
+fn split_quoted(value: &str, separator: char) -> impl Iterator<Item = &str> {
+	let mut quoted = false;
+	let mut escaped = false;
+
+	value
+		.split(move |c| {
+			match c {
+				_ if escaped => escaped = false,
+				'\\' if quoted => escaped = true,
+				'"' => quoted = !quoted,
+				_ => {}
+			}
+
+			c == separator && !quoted
+		})
+		.map(str::trim)
+		.filter(|item| !item.is_empty())
+}
+
+fn unquote(value: &str) -> String {
+	let Some(inner) = value
+		.strip_prefix('"')
+		.and_then(|value| value.strip_suffix('"'))
+	else {
+		return value.to_string();
+	};
+
+	let mut unquoted = String::with_capacity(inner.len());
+	let mut chars = inner.chars();
+
+	while let Some(c) = chars.next() {
+		match c {
+			'\\' => unquoted.extend(chars.next()),
+			c => unquoted.push(c),
+		}
+	}
+
+	unquoted
+}
+
+fn quote(value: &str) -> String {
+	if is_valid_name(value) {
+		return value.to_string();
+	}
+
+	format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// The media type of the body, such as `text/html; charset=utf-8`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContentType {
+	mime: String,
+	params: Vec<(String, String)>,
+}
+
+impl ContentType {
+	/// Creates a content type from a `type/subtype` media type.
+	#[must_use]
+	pub fn new<M: Into<String>>(mime: M) -> Self {
+		let mut mime = mime.into();
+
+		mime.make_ascii_lowercase();
+
+		Self {
+			mime,
+			params: Vec::new(),
+		}
+	}
+
+	#[must_use]
+	pub fn json() -> Self {
+		Self::new("application/json")
+	}
+
+	#[must_use]
+	pub fn xml() -> Self {
+		Self::new("application/xml")
+	}
+
+	#[must_use]
+	pub fn form() -> Self {
+		Self::new("application/x-www-form-urlencoded")
+	}
+
+	#[must_use]
+	pub fn html() -> Self {
+		Self::new("text/html").param("charset", "utf-8")
+	}
+
+	#[must_use]
+	pub fn plain() -> Self {
+		Self::new("text/plain")
+	}
+
+	#[must_use]
+	pub fn octet_stream() -> Self {
+		Self::new("application/octet-stream")
+	}
+
+	/// Adds a parameter, such as `charset`.
+	#[must_use]
+	pub fn param<N: Into<String>, V: Into<String>>(mut self, name: N, value: V) -> Self {
+		let mut name = name.into();
+
+		name.make_ascii_lowercase();
+		self.params.push((name, value.into()));
+		self
+	}
+
+	/// Returns the media type without parameters, in lowercase.
+	#[must_use]
+	pub fn mime(&self) -> &str {
+		&self.mime
+	}
+
+	#[must_use]
+	pub fn get_param(&self, name: &str) -> Option<&str> {
+		self.params
+			.iter()
+			.find(|(n, _)| n.eq_ignore_ascii_case(name))
+			.map(|(_, value)| value.as_str())
+	}
+
+	#[must_use]
+	pub fn charset(&self) -> Option<&str> {
+		self.get_param("charset")
+	}
+}
+
+impl TypedHeader for ContentType {
+	const NAME: &'static str = super::CONTENT_TYPE;
+
+	fn decode(value: &str) -> Option<Self> {
+		let (mime, params) = value.split_once(';').unwrap_or((value, ""));
+		let mime = mime.trim();
+		let (kind, subtype) = mime.split_once('/')?;
+
+		if !is_valid_name(kind) || !is_valid_name(subtype) {
+			return None;
+		}
+
+		let mut content_type = Self::new(mime);
+
+		for param in split_quoted(params, ';') {
+			let (name, value) = param.split_once('=')?;
+
+			content_type = content_type.param(name.trim(), unquote(value.trim()));
+		}
+
+		Some(content_type)
+	}
+
+	fn encode(&self) -> String {
+		let mut value = self.mime.clone();
+
+		for (name, param) in &self.params {
+			let _ = write!(value, "; {name}={}", quote(param));
+		}
+
+		value
+	}
+}
+
+/// The length of the body in bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContentLength(pub u64);
+
+impl TypedHeader for ContentLength {
+	const NAME: &'static str = super::CONTENT_LENGTH;
+	// repeated values are only accepted if they are all the same
+	const LIST: bool = true;
+
+	fn decode(value: &str) -> Option<Self> {
+		let mut values = value
+			.split(',')
+			.map(str::trim)
+			.map(|v| v.parse().ok().filter(|_| super::is_valid_length(v)));
+		let first = values.next()??;
+
+		values
+			.all(|value| value == Some(first))
+			.then_some(Self(first))
+	}
+
+	fn encode(&self) -> String {
+		self.0.to_string()
+	}
+}
+
+/// The credentials used to authenticate a request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Authorization {
+	Basic { username: String, password: String },
+	Bearer(String),
+	Other { scheme: String, credentials: String },
+}
+
+impl Authorization {
+	#[must_use]
+	pub fn basic<U: Into<String>, P: Into<String>>(username: U, password: P) -> Self {
+		Self::Basic {
+			username: username.into(),
+			password: password.into(),
+		}
+	}
+
+	#[must_use]
+	pub fn bearer<T: Into<String>>(token: T) -> Self {
+		Self::Bearer(token.into())
+	}
+}
+
+impl TypedHeader for Authorization {
+	const NAME: &'static str = super::AUTHORIZATION;
+
+	fn decode(value: &str) -> Option<Self> {
+		let (scheme, credentials) = value.trim().split_once(' ')?;
+		let credentials = credentials.trim();
+
+		Some(if scheme.eq_ignore_ascii_case("basic") {
+			let decoded = String::from_utf8(STANDARD.decode(credentials).ok()?).ok()?;
+			let (username, password) = decoded.split_once(':')?;
+
+			Self::basic(username, password)
+		} else if scheme.eq_ignore_ascii_case("bearer") {
+			Self::Bearer(credentials.to_string())
+		} else {
+			Self::Other {
+				scheme: scheme.to_string(),
+				credentials: credentials.to_string(),
+			}
+		})
+	}
+
+	fn encode(&self) -> String {
+		match self {
+			Self::Basic { username, password } => {
+				format!(
+					"Basic {}",
+					STANDARD.encode(format!("{username}:{password}"))
+				)
+			}
+			Self::Bearer(token) => format!("Bearer {token}"),
+			Self::Other {
+				scheme,
+				credentials,
+			} => format!("{scheme} {credentials}"),
+		}
+	}
+}
+
+/// The media types accepted in the response, ranked by their q-value.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Accept {
+	items: Vec<(String, f32)>,
+}
+
+impl Accept {
+	#[must_use]
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Adds a media type with a q-value between 0 and 1.
+	#[must_use]
+	pub fn item<M: Into<String>>(mut self, mime: M, quality: f32) -> Self {
+		self.items.push((mime.into(), quality.clamp(0.0, 1.0)));
+		self
+	}
+
+	/// Returns the media types and their q-values, from most to least preferred.
+	pub fn iter(&self) -> impl Iterator<Item = (&str, f32)> {
+		let mut items = self
+			.items
+			.iter()
+			.map(|(mime, quality)| (mime.as_str(), *quality))
+			.collect::<Vec<_>>();
+
+		// the sort is stable, so equal q-values keep their order
+		items.sort_by(|a, b| b.1.total_cmp(&a.1));
+		items.into_iter()
+	}
+
+	/// Returns the q-value of a media type, using the most specific matching
+	/// range, or 0 if it is not accepted.
+	#[must_use]
+	pub fn quality(&self, mime: &str) -> f32 {
+		let kind = mime.split('/').next().unwrap_or_default();
+
+		self.items
+			.iter()
+			.filter_map(|(range, quality)| {
+				let range = range.split(';').next().unwrap_or_default().trim();
+				let specificity = if range.eq_ignore_ascii_case(mime) {
+					2
+				} else if range
+					.strip_suffix("/*")
+					.is_some_and(|k| k.eq_ignore_ascii_case(kind))
+				{
+					1
+				} else if range == "*/*" {
+					0
+				} else {
+					return None;
+				};
+
+				Some((specificity, *quality))
+			})
+			.max_by_key(|(specificity, _)| *specificity)
+			.map_or(0.0, |(_, quality)| quality)
+	}
+}
+
+impl TypedHeader for Accept {
+	const NAME: &'static str = super::ACCEPT;
+	const LIST: bool = true;
+
+	fn decode(value: &str) -> Option<Self> {
+		let mut accept = Self::new();
+
+		for item in split_list(value) {
+			let mut quality = 1.0;
+			let mut mime = Vec::new();
+
+			for (i, part) in split_quoted(item, ';').enumerate() {
+				match part.split_once('=') {
+					Some((name, q)) if i > 0 && name.trim().eq_ignore_ascii_case("q") => {
+						quality = q.trim().parse().ok()?;
+					}
+					_ => mime.push(part),
+				}
+			}
+
+			accept = accept.item(mime.join(";"), quality);
+		}
+
+		Some(accept)
+	}
+
+	fn encode(&self) -> String {
+		self.items
+			.iter()
+			.map(|(mime, quality)| {
+				if *quality >= 1.0 {
+					mime.clone()
+				} else {
+					format!("{mime};q={quality}")
+				}
+			})
+			.collect::<Vec<_>>()
+			.join(", ")
+	}
+}
+
+/// Directives that control how a response is cached.
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CacheControl {
+	pub no_cache: bool,
+	pub no_store: bool,
+	pub no_transform: bool,
+	pub must_revalidate: bool,
+	pub public: bool,
+	pub private: bool,
+	pub immutable: bool,
+	pub max_age: Option<u64>,
+	pub s_max_age: Option<u64>,
+	/// Directives that are not known, with their value if they have one.
+	pub extensions: Vec<(String, Option<String>)>,
+}
+
+impl CacheControl {
+	#[must_use]
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	#[must_use]
+	pub fn no_cache(mut self) -> Self {
+		self.no_cache = true;
+		self
+	}
+
+	#[must_use]
+	pub fn no_store(mut self) -> Self {
+		self.no_store = true;
+		self
+	}
+
+	#[must_use]
+	pub fn public(mut self) -> Self {
+		self.public = true;
+		self
+	}
+
+	#[must_use]
+	pub fn private(mut self) -> Self {
+		self.private = true;
+		self
+	}
+
+	#[must_use]
+	pub fn must_revalidate(mut self) -> Self {
+		self.must_revalidate = true;
+		self
+	}
+
+	#[must_use]
+	pub fn immutable(mut self) -> Self {
+		self.immutable = true;
+		self
+	}
+
+	/// Sets the number of seconds the response stays fresh.
+	#[must_use]
+	pub fn max_age(mut self, seconds: u64) -> Self {
+		self.max_age = Some(seconds);
+		self
+	}
+}
+
+impl TypedHeader for CacheControl {
+	const NAME: &'static str = super::CACHE_CONTROL;
+	const LIST: bool = true;
+
+	fn decode(value: &str) -> Option<Self> {
+		let mut cache_control = Self::new();
+
+		for directive in split_list(value) {
+			let (name, argument) = match directive.split_once('=') {
+				Some((name, argument)) => (name.trim(), Some(unquote(argument.trim()))),
+				None => (directive, None),
+			};
+
+			match (name.to_ascii_lowercase().as_str(), argument) {
+				("no-cache", _) => cache_control.no_cache = true,
+				("no-store", _) => cache_control.no_store = true,
+				("no-transform", _) => cache_control.no_transform = true,
+				("must-revalidate", _) => cache_control.must_revalidate = true,
+				("public", _) => cache_control.public = true,
+				("private", _) => cache_control.private = true,
+				("immutable", _) => cache_control.immutable = true,
+				("max-age", Some(seconds)) => cache_control.max_age = Some(seconds.parse().ok()?),
+				("s-maxage", Some(seconds)) => {
+					cache_control.s_max_age = Some(seconds.parse().ok()?);
+				}
+				(_, argument) => cache_control.extensions.push((name.to_string(), argument)),
+			}
+		}
+
+		Some(cache_control)
+	}
+
+	fn encode(&self) -> String {
+		let flags = [
+			(self.no_cache, "no-cache"),
+			(self.no_store, "no-store"),
+			(self.no_transform, "no-transform"),
+			(self.must_revalidate, "must-revalidate"),
+			(self.public, "public"),
+			(self.private, "private"),
+			(self.immutable, "immutable"),
+		];
+
+		let mut directives = flags
+			.into_iter()
+			.filter(|(set, _)| *set)
+			.map(|(_, name)| name.to_string())
+			.collect::<Vec<_>>();
+
+		if let Some(seconds) = self.max_age {
+			directives.push(format!("max-age={seconds}"));
+		}
+
+		if let Some(seconds) = self.s_max_age {
+			directives.push(format!("s-maxage={seconds}"));
+		}
+
+		for (name, argument) in &self.extensions {
+			directives.push(match argument {
+				Some(argument) => format!("{name}={}", quote(argument)),
+				None => name.clone(),
+			});
+		}
+
+		directives.join(", ")
+	}
+}
+
+/// An identifier for a specific version of a resource.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ETag {
+	tag: String,
+	weak: bool,
+}
+
+impl ETag {
+	/// Creates a strong entity tag, which must not contain `"`.
+	#[must_use]
+	pub fn strong<T: Into<String>>(tag: T) -> Self {
+		Self {
+			tag: tag.into(),
+			weak: false,
+		}
+	}
+
+	/// Creates a weak entity tag, which must not contain `"`.
+	#[must_use]
+	pub fn weak<T: Into<String>>(tag: T) -> Self {
+		Self {
+			tag: tag.into(),
+			weak: true,
+		}
+	}
+
+	#[must_use]
+	pub fn tag(&self) -> &str {
+		&self.tag
+	}
+
+	#[must_use]
+	pub fn is_weak(&self) -> bool {
+		self.weak
+	}
+
+	/// Compares the tags, where both must be strong.
+	#[must_use]
+	pub fn strong_eq(&self, other: &Self) -> bool {
+		!self.weak && !other.weak && self.tag == other.tag
+	}
+
+	/// Compares the tags, ignoring whether either is weak.
+	#[must_use]
+	pub fn weak_eq(&self, other: &Self) -> bool {
+		self.tag == other.tag
+	}
+}
+
+impl TypedHeader for ETag {
+	const NAME: &'static str = super::ETAG;
+
+	fn decode(value: &str) -> Option<Self> {
+		let value = value.trim();
+		let (weak, quoted) = match value.strip_prefix("W/") {
+			Some(quoted) => (true, quoted),
+			None => (false, value),
+		};
+
+		let tag = quoted.strip_prefix('"')?.strip_suffix('"')?;
+
+		(!tag.contains('"')).then(|| Self {
+			tag: tag.to_string(),
+			weak,
+		})
+	}
+
+	fn encode(&self) -> String {
+		if self.weak {
+			format!("W/\"{}\"", self.tag)
+		} else {
+			format!("\"{}\"", self.tag)
+		}
+	}
+}
+
+/// The entity tags of the versions that a client already has.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IfNoneMatch {
+	Any,
+	Tags(Vec<ETag>),
+}
+
+impl IfNoneMatch {
+	/// Returns `true` if the entity tag matches, using the weak comparison.
+	///
+	/// A matching tag means the client's version is current, so a `GET`
+	/// should be answered with `304 Not Modified`.
+	#[must_use]
+	pub fn matches(&self, etag: &ETag) -> bool {
+		match self {
+			Self::Any => true,
+			Self::Tags(tags) => tags.iter().any(|tag| tag.weak_eq(etag)),
+		}
+	}
+}
+
+impl TypedHeader for IfNoneMatch {
+	const NAME: &'static str = super::IF_NONE_MATCH;
+	const LIST: bool = true;
+
+	fn decode(value: &str) -> Option<Self> {
+		if value.trim() == "*" {
+			return Some(Self::Any);
+		}
+
+		split_list(value)
+			.map(ETag::decode)
+			.collect::<Option<_>>()
+			.map(Self::Tags)
+	}
+
+	fn encode(&self) -> String {
+		match self {
+			Self::Any => "*".to_string(),
+			Self::Tags(tags) => tags.iter().map(ETag::encode).collect::<Vec<_>>().join(", "),
+		}
+	}
+}
+
+/// The time the message was created, formatted as an HTTP-date.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Date(pub SystemTime);
+
+impl TypedHeader for Date {
+	const NAME: &'static str = super::DATE;
+
+	fn decode(value: &str) -> Option<Self> {
+		httpdate::parse_http_date(value.trim()).ok().map(Self)
+	}
+
+	fn encode(&self) -> String {
+		httpdate::fmt_http_date(self.0)
+	}
+}
+
+/// The time the resource was last changed, formatted as an HTTP-date.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LastModified(pub SystemTime);
+
+impl TypedHeader for LastModified {
+	const NAME: &'static str = super::LAST_MODIFIED;
+
+	fn decode(value: &str) -> Option<Self> {
+		httpdate::parse_http_date(value.trim()).ok().map(Self)
+	}
+
+	fn encode(&self) -> String {
+		httpdate::fmt_http_date(self.0)
+	}
+}
+
+/// How long to wait before making a follow-up request, as a number of
+/// seconds or an HTTP-date.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RetryAfter {
+	Delay(Duration),
+	Date(SystemTime),
+}
+
+impl RetryAfter {
+	/// Returns the time left to wait from now, which is zero for a date in the past.
+	#[must_use]
+	pub fn delay(&self) -> Duration {
+		match self {
+			Self::Delay(delay) => *delay,
+			Self::Date(date) => date.duration_since(SystemTime::now()).unwrap_or_default(),
+		}
+	}
+}
+
+impl TypedHeader for RetryAfter {
+	const NAME: &'static str = super::RETRY_AFTER;
+
+	fn decode(value: &str) -> Option<Self> {
+		let value = value.trim();
+
+		if value.bytes().all(|b| b.is_ascii_digit()) {
+			return value.parse().ok().map(Duration::from_secs).map(Self::Delay);
+		}
+
+		httpdate::parse_http_date(value).ok().map(Self::Date)
+	}
+
+	fn encode(&self) -> String {
+		match self {
+			Self::Delay(delay) => delay.as_secs().to_string(),
+			Self::Date(date) => httpdate::fmt_http_date(*date),
+		}
+	}
+}
+
+/// The URL to redirect to, which may be relative to the request URL.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Location(pub String);
+
+impl TypedHeader for Location {
+	const NAME: &'static str = super::LOCATION;
+
+	fn decode(value: &str) -> Option<Self> {
+		Some(Self(value.trim().to_string()))
+	}
+
+	fn encode(&self) -> String {
+		self.0.clone()
+	}
+}
+
+/// The host and optional port of the request target.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Host {
+	pub host: String,
+	pub port: Option<u16>,
+}
+
+impl TypedHeader for Host {
+	const NAME: &'static str = super::HOST;
+
+	fn decode(value: &str) -> Option<Self> {
+		let value = value.trim();
+
+		// IPv6 addresses are enclosed in brackets, since they contain colons
+		let (host, port) = match value.rfind(':') {
+			Some(i) if !value[i..].contains(']') => {
+				(&value[..i], Some(value[i + 1..].parse().ok()?))
+			}
+			_ => (value, None),
+		};
+
+		(!host.is_empty()).then(|| Self {
+			host: host.to_string(),
+			port,
+		})
+	}
+
+	fn encode(&self) -> String {
+		match self.port {
+			Some(port) => format!("{}:{port}", self.host),
+			None => self.host.clone(),
+		}
+	}
+}
+
+/// The software that sent the request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UserAgent(pub String);
+
+impl TypedHeader for UserAgent {
+	const NAME: &'static str = super::USER_AGENT;
+
+	fn decode(value: &str) -> Option<Self> {
+		Some(Self(value.trim().to_string()))
+	}
+
+	fn encode(&self) -> String {
+		self.0.clone()
+	}
+}
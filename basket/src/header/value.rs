@@ -0,0 +1,90 @@
+use std::{borrow::Cow, fmt};
+
+/// The value of a header, which is usually text but may contain any
+/// non-ASCII bytes (obs-text) when received from a peer.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct HeaderValue<'a>(Cow<'a, [u8]>);
+
+impl HeaderValue<'_> {
+	#[must_use]
+	pub const fn from_static(value: &'static str) -> HeaderValue<'static> {
+		HeaderValue(Cow::Borrowed(value.as_bytes()))
+	}
+
+	#[must_use]
+	pub fn as_bytes(&self) -> &[u8] {
+		&self.0
+	}
+
+	/// Returns the value as a string, or `None` if it is not valid UTF-8.
+	#[must_use]
+	pub fn to_str(&self) -> Option<&str> {
+		std::str::from_utf8(&self.0).ok()
+	}
+
+	#[must_use]
+	pub fn into_owned(self) -> HeaderValue<'static> {
+		HeaderValue(Cow::Owned(self.0.into_owned()))
+	}
+}
+
+impl fmt::Debug for HeaderValue<'_> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self.to_str() {
+			Some(value) => fmt::Debug::fmt(value, f),
+			None => write!(f, "b\"{}\"", self.0.escape_ascii()),
+		}
+	}
+}
+
+/// Formats the value, replacing bytes that are not valid UTF-8.
+impl fmt::Display for HeaderValue<'_> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(&String::from_utf8_lossy(&self.0))
+	}
+}
+
+impl PartialEq<str> for HeaderValue<'_> {
+	fn eq(&self, other: &str) -> bool {
+		self.as_bytes() == other.as_bytes()
+	}
+}
+
+impl PartialEq<&str> for HeaderValue<'_> {
+	fn eq(&self, other: &&str) -> bool {
+		self.as_bytes() == other.as_bytes()
+	}
+}
+
+impl<'a> From<&'a str> for HeaderValue<'a> {
+	fn from(value: &'a str) -> Self {
+		Self(Cow::Borrowed(value.as_bytes()))
+	}
+}
+
+impl From<String> for HeaderValue<'_> {
+	fn from(value: String) -> Self {
+		Self(Cow::Owned(value.into_bytes()))
+	}
+}
+
+impl<'a> From<Cow<'a, str>> for HeaderValue<'a> {
+	fn from(value: Cow<'a, str>) -> Self {
+		match value {
+			Cow::Borrowed(value) => value.into(),
+			Cow::Owned(value) => value.into(),
+		}
+	}
+}
+
+impl<'a> From<&'a [u8]> for HeaderValue<'a> {
+	fn from(value: &'a [u8]) -> Self {
+		Self(Cow::Borrowed(value))
+	}
+}
+
+impl From<Vec<u8>> for HeaderValue<'_> {
+	fn from(value: Vec<u8>) -> Self {
+		Self(Cow::Owned(value))
+	}
+}
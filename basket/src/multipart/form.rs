@@ -0,0 +1,313 @@
+use std::{
+	collections::{hash_map::RandomState, VecDeque},
+	fmt,
+	fs::File,
+	hash::{BuildHasher, Hasher},
+	io::{self, Cursor, Read},
+	path::Path,
+};
+
+use crate::{header, Error};
+
+/// Content types guessed from the extension of files added with [`Form::file`].
+const CONTENT_TYPES: &[(&str, &str)] = &[
+	("css", "text/css"),
+	("csv", "text/csv"),
+	("gif", "image/gif"),
+	("html", "text/html"),
+	("jpeg", "image/jpeg"),
+	("jpg", "image/jpeg"),
+	("js", "text/javascript"),
+	("json", "application/json"),
+	("pdf", "application/pdf"),
+	("png", "image/png"),
+	("svg", "image/svg+xml"),
+	("txt", "text/plain"),
+	("wasm", "application/wasm"),
+	("webp", "image/webp"),
+	("xml", "application/xml"),
+	("zip", "application/zip"),
+];
+
+enum Body {
+	Bytes(Vec<u8>),
+	Reader {
+		reader: Box<dyn Read + Send>,
+		length: Option<u64>,
+	},
+}
+
+/// A single part of a [`Form`].
+pub struct Part {
+	body: Body,
+	file_name: Option<String>,
+	content_type: Option<String>,
+}
+
+impl fmt::Debug for Part {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("Part")
+			.field("file_name", &self.file_name)
+			.field("content_type", &self.content_type)
+			.finish_non_exhaustive()
+	}
+}
+
+impl Part {
+	pub fn text<T: Into<String>>(value: T) -> Self {
+		Self::bytes(value.into())
+	}
+
+	pub fn bytes<B: Into<Vec<u8>>>(value: B) -> Self {
+		Self {
+			body: Body::Bytes(value.into()),
+			file_name: None,
+			content_type: None,
+		}
+	}
+
+	/// Creates a part that is streamed from the reader while the request is sent.
+	///
+	/// Since the length is not known, the request is sent with chunked
+	/// transfer encoding. Use [`Part::reader_with_length`] if the length is known.
+	pub fn reader<R: Read + Send + 'static>(reader: R) -> Self {
+		Self {
+			body: Body::Reader {
+				reader: Box::new(reader),
+				length: None,
+			},
+			file_name: None,
+			content_type: None,
+		}
+	}
+
+	/// Creates a part that is streamed from the reader, which must produce
+	/// exactly `length` bytes.
+	pub fn reader_with_length<R: Read + Send + 'static>(reader: R, length: u64) -> Self {
+		Self {
+			body: Body::Reader {
+				reader: Box::new(reader),
+				length: Some(length),
+			},
+			file_name: None,
+			content_type: None,
+		}
+	}
+
+	#[must_use]
+	pub fn file_name<N: Into<String>>(mut self, file_name: N) -> Self {
+		self.file_name = Some(file_name.into());
+		self
+	}
+
+	/// Sets the content type of the part.
+	///
+	/// A content type that is not a valid header value, such as one with a
+	/// line break, makes the request fail when it is sent.
+	#[must_use]
+	pub fn content_type<C: Into<String>>(mut self, content_type: C) -> Self {
+		self.content_type = Some(content_type.into());
+		self
+	}
+
+	fn length(&self) -> Option<u64> {
+		match &self.body {
+			Body::Bytes(bytes) => Some(bytes.len() as u64),
+			Body::Reader { length, .. } => *length,
+		}
+	}
+}
+
+/// A `multipart/form-data` body, sent with [`RequestBuilder::multipart`](crate::RequestBuilder::multipart).
+#[derive(Debug)]
+pub struct Form {
+	boundary: String,
+	parts: Vec<(String, Part)>,
+}
+
+impl Default for Form {
+	fn default() -> Self {
+		// the boundary only needs to be unlikely to appear in the body,
+		// so the randomly seeded hasher is enough
+		let state = RandomState::new();
+		let boundary = format!(
+			"basket-{:016x}{:016x}",
+			state.build_hasher().finish(),
+			state.hash_one(std::process::id())
+		);
+
+		Self {
+			boundary,
+			parts: Vec::new(),
+		}
+	}
+}
+
+impl Form {
+	#[must_use]
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	#[must_use]
+	pub fn boundary(&self) -> &str {
+		&self.boundary
+	}
+
+	/// Returns the `content-type` header value for the form.
+	#[must_use]
+	pub fn content_type(&self) -> String {
+		format!("multipart/form-data; boundary={}", self.boundary)
+	}
+
+	/// Adds a text field.
+	#[must_use]
+	pub fn text<N: Into<String>, V: Into<String>>(self, name: N, value: V) -> Self {
+		self.part(name, Part::text(value))
+	}
+
+	#[must_use]
+	pub fn part<N: Into<String>>(mut self, name: N, part: Part) -> Self {
+		self.parts.push((name.into(), part));
+		self
+	}
+
+	/// Adds a file part that is streamed from disk while the request is sent.
+	///
+	/// The file name is taken from the path, and the content type is guessed
+	/// from its extension.
+	///
+	/// # Errors
+	/// - If the file could not be opened.
+	pub fn file<N: Into<String>, P: AsRef<Path>>(self, name: N, path: P) -> io::Result<Self> {
+		let path = path.as_ref();
+		let file = File::open(path)?;
+		let length = file.metadata()?.len();
+		let content_type = path
+			.extension()
+			.and_then(|extension| extension.to_str())
+			.and_then(|extension| {
+				CONTENT_TYPES
+					.iter()
+					.find(|(ext, _)| ext.eq_ignore_ascii_case(extension))
+			})
+			.map_or("application/octet-stream", |(_, content_type)| content_type);
+
+		let mut part = Part::reader_with_length(file, length).content_type(content_type);
+
+		if let Some(file_name) = path.file_name() {
+			part = part.file_name(file_name.to_string_lossy());
+		}
+
+		Ok(self.part(name, part))
+	}
+
+	/// Checks that every content type is a valid header value, so that none
+	/// can add headers of its own to a part.
+	pub(crate) fn validate(&self) -> Result<(), Error> {
+		let valid = self.parts.iter().all(|(_, part)| {
+			part.content_type
+				.as_ref()
+				.is_none_or(|content_type| header::is_valid_value(content_type.as_bytes()))
+		});
+
+		if valid {
+			Ok(())
+		} else {
+			Err(Error::InvalidHeader(header::CONTENT_TYPE.to_string()))
+		}
+	}
+
+	/// Returns the encoded form if every part is held in memory, so that it
+	/// can be sent again, or the form itself if any part is read from a reader.
+	pub(crate) fn into_bytes(self) -> Result<Vec<u8>, Self> {
+		if !self
+			.parts
+			.iter()
+			.all(|(_, part)| matches!(part.body, Body::Bytes(_)))
+		{
+			return Err(self);
+		}
+
+		let (mut reader, _) = self.into_reader();
+		let mut bytes = Vec::new();
+
+		// every segment is a cursor over memory, so reading cannot fail
+		let _ = reader.read_to_end(&mut bytes);
+
+		Ok(bytes)
+	}
+
+	/// Returns a reader over the encoded form, along with its length if
+	/// the length of every part is known.
+	pub(crate) fn into_reader(self) -> (FormReader, Option<u64>) {
+		let mut length = Some(0u64);
+		let mut segments = VecDeque::with_capacity(self.parts.len() * 2 + 1);
+
+		for (name, part) in self.parts {
+			let mut head = format!(
+				"--{}\r\ncontent-disposition: form-data; name=\"{}\"",
+				self.boundary,
+				escape(&name)
+			);
+
+			if let Some(file_name) = &part.file_name {
+				head.push_str("; filename=\"");
+				head.push_str(&escape(file_name));
+				head.push('"');
+			}
+
+			if let Some(content_type) = &part.content_type {
+				head.push_str("\r\ncontent-type: ");
+				head.push_str(content_type);
+			}
+
+			head.push_str("\r\n\r\n");
+
+			length = length
+				.zip(part.length())
+				.map(|(length, part)| length + head.len() as u64 + part + 2);
+
+			segments.push_back(Box::new(Cursor::new(head)) as Box<dyn Read + Send>);
+			segments.push_back(match part.body {
+				Body::Bytes(bytes) => Box::new(Cursor::new(bytes).chain(&b"\r\n"[..])),
+				Body::Reader { reader, .. } => Box::new(reader.chain(&b"\r\n"[..])),
+			});
+		}
+
+		let tail = format!("--{}--\r\n", self.boundary);
+
+		length = length.map(|length| length + tail.len() as u64);
+		segments.push_back(Box::new(Cursor::new(tail)));
+
+		(FormReader { segments }, length)
+	}
+}
+
+/// Escapes a quoted parameter in the same way as browsers do.
+fn escape(value: &str) -> String {
+	value
+		.replace('"', "%22")
+		.replace('\r', "%0D")
+		.replace('\n', "%0A")
+}
+
+/// Reads each segment of an encoded [`Form`] in turn.
+pub(crate) struct FormReader {
+	segments: VecDeque<Box<dyn Read + Send>>,
+}
+
+impl Read for FormReader {
+	fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+		while let Some(segment) = self.segments.front_mut() {
+			match segment.read(buf)? {
+				0 if !buf.is_empty() => {
+					self.segments.pop_front();
+				}
+				n => return Ok(n),
+			}
+		}
+
+		Ok(0)
+	}
+}
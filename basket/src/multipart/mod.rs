@@ -0,0 +1,25 @@
+mod form;
+mod parser;
+
+pub use form::{Form, Part};
+pub use parser::{Field, Multipart};
+
+/// Returns the boundary parameter of a `multipart/*` content type.
+#[must_use]
+pub fn boundary(content_type: &str) -> Option<&str> {
+	let mut params = content_type.split(';');
+	let mime = params.next()?.trim();
+
+	if !mime
+		.get(..10)
+		.is_some_and(|prefix| prefix.eq_ignore_ascii_case("multipart/"))
+	{
+		return None;
+	}
+
+	params
+		.filter_map(|param| param.split_once('='))
+		.find(|(name, _)| name.trim().eq_ignore_ascii_case("boundary"))
+		.map(|(_, value)| value.trim().trim_matches('"'))
+		.filter(|boundary| (1..=70).contains(&boundary.len()))
+}
@@ -0,0 +1,302 @@
+use std::io::{self, Read};
+
+use crate::{header, Error, Header, HeaderMap};
+
+/// The largest block of headers accepted for a single part.
+const MAX_HEADERS_LEN: usize = 8 * 1024;
+const CHUNK_LEN: usize = 8 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+	/// Reading the body of a part, or the preamble before the first part.
+	Body,
+	/// A delimiter was read, and is followed by a part or the end of the body.
+	Delimiter,
+	Done,
+}
+
+/// A streaming parser over the parts of a `multipart/form-data` body.
+///
+/// Only a small buffer is held in memory, so parts can be much larger than
+/// the memory available, as long as they are within the size limits.
+#[derive(Debug)]
+pub struct Multipart<R> {
+	reader: R,
+	buf: Vec<u8>,
+	/// The `\r\n--boundary` sequence that ends each part.
+	delimiter: Vec<u8>,
+	state: State,
+	part_limit: u64,
+	total_limit: u64,
+	part_len: u64,
+	total_len: u64,
+}
+
+impl<R: Read> Multipart<R> {
+	/// Creates a parser over the reader, with a limit of 16 MiB per part
+	/// and 64 MiB in total.
+	pub fn new(reader: R, boundary: &str) -> Self {
+		Self {
+			reader,
+			// the first delimiter is not preceded by a line break, so one is
+			// added to find every delimiter in the same way
+			buf: b"\r\n".to_vec(),
+			delimiter: format!("\r\n--{boundary}").into_bytes(),
+			state: State::Body,
+			part_limit: 16 * 1024 * 1024,
+			total_limit: 64 * 1024 * 1024,
+			part_len: 0,
+			total_len: 0,
+		}
+	}
+
+	/// Sets the maximum size of the body of a single part.
+	#[must_use]
+	pub fn part_limit(mut self, limit: u64) -> Self {
+		self.part_limit = limit;
+		self
+	}
+
+	/// Sets the maximum size of the whole multipart body.
+	#[must_use]
+	pub fn total_limit(mut self, limit: u64) -> Self {
+		self.total_limit = limit;
+		self
+	}
+
+	/// Returns the next part, skipping the rest of the previous part if it
+	/// was not read to the end.
+	///
+	/// # Errors
+	/// - If the body is not valid multipart data.
+	/// - If the body exceeds the total size limit.
+	/// - If the body could not be read.
+	pub fn next_field(&mut self) -> Result<Option<Field<'_, R>>, Error> {
+		let mut scratch = [0; CHUNK_LEN];
+
+		while self.state == State::Body {
+			self.read_body(&mut scratch, false)?;
+		}
+
+		if self.state == State::Done {
+			return Ok(None);
+		}
+
+		// transport padding is allowed between the boundary and the line break
+		let headers_end = loop {
+			let start = self
+				.buf
+				.iter()
+				.position(|&b| b != b' ' && b != b'\t')
+				.unwrap_or(self.buf.len());
+
+			if self.buf[start..].starts_with(b"--") {
+				self.state = State::Done;
+
+				return Ok(None);
+			}
+
+			if let Some(end) = find(&self.buf[start..], b"\r\n\r\n") {
+				self.buf.drain(..start);
+
+				break end;
+			}
+
+			if self.buf.len() > MAX_HEADERS_LEN || !self.fill()? {
+				return Err(Error::InvalidMultipart);
+			}
+		};
+
+		let block = std::str::from_utf8(&self.buf[..headers_end])?;
+		let mut headers = HeaderMap::new();
+
+		for line in block.split("\r\n").filter(|line| !line.is_empty()) {
+			let (name, value) = line.split_once(':').ok_or(Error::InvalidMultipart)?;
+
+			headers.append(Header {
+				name: name.trim().to_ascii_lowercase().into(),
+				value: value.trim().to_string().into(),
+			});
+		}
+
+		self.buf.drain(..headers_end + 4);
+
+		let disposition = headers
+			.get("content-disposition")
+			.ok_or(Error::InvalidMultipart)?;
+		let name = parameter(disposition, "name").ok_or(Error::InvalidMultipart)?;
+		let file_name = parameter(disposition, "filename");
+
+		self.state = State::Body;
+		self.part_len = 0;
+
+		Ok(Some(Field {
+			name,
+			file_name,
+			headers,
+			multipart: self,
+		}))
+	}
+
+	/// Reads more of the body into the buffer, returning `false` at the end.
+	fn fill(&mut self) -> Result<bool, Error> {
+		let mut chunk = [0; CHUNK_LEN];
+		let n = loop {
+			match self.reader.read(&mut chunk) {
+				Ok(n) => break n,
+				Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+				Err(e) => return Err(e.into()),
+			}
+		};
+
+		self.total_len += n as u64;
+
+		if self.total_len > self.total_limit {
+			return Err(Error::MultipartTooLarge);
+		}
+
+		self.buf.extend_from_slice(&chunk[..n]);
+
+		Ok(n > 0)
+	}
+
+	/// Reads the body of the current part, returning 0 once the delimiter is reached.
+	fn read_body(&mut self, out: &mut [u8], limited: bool) -> Result<usize, Error> {
+		if self.state != State::Body || out.is_empty() {
+			return Ok(0);
+		}
+
+		loop {
+			let available = match find(&self.buf, &self.delimiter) {
+				Some(0) => {
+					self.buf.drain(..self.delimiter.len());
+					self.state = State::Delimiter;
+
+					return Ok(0);
+				}
+				Some(position) => position,
+				// the end of the buffer could be the start of a delimiter
+				None => self.buf.len().saturating_sub(self.delimiter.len() - 1),
+			};
+
+			if available == 0 {
+				if !self.fill()? {
+					return Err(Error::InvalidMultipart);
+				}
+
+				continue;
+			}
+
+			let n = available.min(out.len());
+
+			out[..n].copy_from_slice(&self.buf[..n]);
+			self.buf.drain(..n);
+
+			if limited {
+				self.part_len += n as u64;
+
+				if self.part_len > self.part_limit {
+					return Err(Error::PartTooLarge);
+				}
+			}
+
+			return Ok(n);
+		}
+	}
+}
+
+/// A part of a multipart body, which reads its content from the body.
+#[derive(Debug)]
+pub struct Field<'m, R> {
+	name: String,
+	file_name: Option<String>,
+	headers: HeaderMap<'static>,
+	multipart: &'m mut Multipart<R>,
+}
+
+impl<R: Read> Field<'_, R> {
+	#[must_use]
+	pub fn name(&self) -> &str {
+		&self.name
+	}
+
+	#[must_use]
+	pub fn file_name(&self) -> Option<&str> {
+		self.file_name.as_deref()
+	}
+
+	#[must_use]
+	pub fn content_type(&self) -> Option<&str> {
+		self.header(header::CONTENT_TYPE)
+	}
+
+	#[must_use]
+	pub fn header(&self, name: &str) -> Option<&str> {
+		self.headers.get(name)
+	}
+
+	#[must_use]
+	pub fn headers(&self) -> &HeaderMap<'static> {
+		&self.headers
+	}
+
+	/// Reads the rest of the part into memory.
+	///
+	/// # Errors
+	/// - If the part exceeds the size limit.
+	/// - If the body is not valid multipart data.
+	pub fn bytes(self) -> Result<Vec<u8>, Error> {
+		let mut bytes = Vec::new();
+		let mut chunk = [0; CHUNK_LEN];
+
+		loop {
+			match self.multipart.read_body(&mut chunk, true)? {
+				0 => return Ok(bytes),
+				n => bytes.extend_from_slice(&chunk[..n]),
+			}
+		}
+	}
+
+	/// Reads the rest of the part into a string.
+	///
+	/// # Errors
+	/// - If the part exceeds the size limit.
+	/// - If the body is not valid multipart data.
+	/// - If the part is not valid UTF-8.
+	pub fn text(self) -> Result<String, Error> {
+		Ok(String::from_utf8(self.bytes()?)?)
+	}
+}
+
+impl<R: Read> Read for Field<'_, R> {
+	fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+		self.multipart
+			.read_body(buf, true)
+			.map_err(|error| match error {
+				Error::Io(e) => e,
+				error => io::Error::new(io::ErrorKind::InvalidData, error),
+			})
+	}
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+	haystack
+		.windows(needle.len())
+		.position(|window| window == needle)
+}
+
+/// Returns a parameter of a `content-disposition` header value.
+fn parameter(value: &str, name: &str) -> Option<String> {
+	value.split(';').skip(1).find_map(|param| {
+		let (key, value) = param.split_once('=')?;
+
+		key.trim().eq_ignore_ascii_case(name).then(|| {
+			value
+				.trim()
+				.trim_matches('"')
+				.replace("%22", "\"")
+				.replace("%0D", "\r")
+				.replace("%0A", "\n")
+		})
+	})
+}
@@ -0,0 +1,102 @@
+mod parser;
+
+pub(crate) use parser::{read_body, read_message, BodyReader};
+#[cfg(feature = "async")]
+pub(crate) use parser::{read_body_async, read_message_async};
+pub use parser::{Event, Head, Parser};
+
+/// Options for parsing requests and responses, including limits that stop
+/// a peer from making the parser allocate without bound.
+///
+/// By default, the request or status line and each header are limited to
+/// 8 KiB, the whole header section to 64 KiB and 100 headers, and the body
+/// is not limited.
+#[derive(Debug, Clone)]
+pub struct ParseConfig {
+	pub(crate) obs_fold: bool,
+	pub(crate) max_start_line: usize,
+	pub(crate) max_header_size: usize,
+	pub(crate) max_headers_size: usize,
+	pub(crate) max_headers: usize,
+	pub(crate) max_body_size: Option<usize>,
+}
+
+impl Default for ParseConfig {
+	fn default() -> Self {
+		Self {
+			obs_fold: false,
+			max_start_line: 8 * 1024,
+			max_header_size: 8 * 1024,
+			max_headers_size: 64 * 1024,
+			max_headers: 100,
+			max_body_size: None,
+		}
+	}
+}
+
+impl ParseConfig {
+	#[must_use]
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Accepts header values folded over multiple lines (obs-fold), replacing
+	/// each fold with a space. When disabled, folded headers are rejected.
+	///
+	/// Responses accept folded headers by default, as required of user agents
+	/// by RFC 9112, while requests reject them.
+	#[must_use]
+	pub fn obs_fold(mut self, enabled: bool) -> Self {
+		self.obs_fold = enabled;
+		self
+	}
+
+	/// Sets the maximum length of the request line or status line, which
+	/// fails with [`Error::StartLineTooLong`](crate::Error::StartLineTooLong).
+	#[must_use]
+	pub fn max_start_line(mut self, limit: usize) -> Self {
+		self.max_start_line = limit;
+		self
+	}
+
+	/// Sets the maximum length of a single header line, which fails with
+	/// [`Error::HeaderTooLarge`](crate::Error::HeaderTooLarge).
+	#[must_use]
+	pub fn max_header_size(mut self, limit: usize) -> Self {
+		self.max_header_size = limit;
+		self
+	}
+
+	/// Sets the maximum length of the whole header section, which fails with
+	/// [`Error::HeadersTooLarge`](crate::Error::HeadersTooLarge).
+	#[must_use]
+	pub fn max_headers_size(mut self, limit: usize) -> Self {
+		self.max_headers_size = limit;
+		self
+	}
+
+	/// Sets the maximum number of headers, which fails with
+	/// [`Error::TooManyHeaders`](crate::Error::TooManyHeaders).
+	#[must_use]
+	pub fn max_headers(mut self, limit: usize) -> Self {
+		self.max_headers = limit;
+		self
+	}
+
+	/// Sets the maximum length of the body, which fails with
+	/// [`Error::BodyTooLarge`](crate::Error::BodyTooLarge).
+	#[must_use]
+	pub fn max_body_size(mut self, limit: usize) -> Self {
+		self.max_body_size = Some(limit);
+		self
+	}
+
+	/// Checks the content length against the body limit.
+	pub(crate) fn check_body(&self, length: usize) -> Result<(), crate::Error> {
+		if self.max_body_size.is_some_and(|limit| length > limit) {
+			return Err(crate::Error::BodyTooLarge);
+		}
+
+		Ok(())
+	}
+}
@@ -0,0 +1,738 @@
+use std::{
+	borrow::Cow,
+	io::{self, BufRead, Read},
+	mem,
+};
+
+#[cfg(feature = "async")]
+use tokio::io::{AsyncBufRead, AsyncBufReadExt};
+
+use crate::{
+	header::{self, FieldParser},
+	Error, HeaderMap, Method, ParseConfig, StatusCode, Version,
+};
+
+/// A complete line along with the number of input bytes it used.
+type Line<'i> = (usize, Cow<'i, [u8]>);
+
+/// The start line and headers of a message, which may borrow from the
+/// buffer they were parsed from.
+#[derive(Debug)]
+pub enum Head<'b> {
+	Request {
+		method: Method,
+		target: Cow<'b, str>,
+		version: Version,
+		headers: HeaderMap<'b>,
+	},
+	Response {
+		version: Version,
+		status: StatusCode,
+		reason: Cow<'b, str>,
+		headers: HeaderMap<'b>,
+	},
+}
+
+impl<'b> Head<'b> {
+	#[must_use]
+	pub fn headers(&self) -> &HeaderMap<'b> {
+		match self {
+			Self::Request { headers, .. } | Self::Response { headers, .. } => headers,
+		}
+	}
+
+	#[must_use]
+	pub fn version(&self) -> Version {
+		match self {
+			Self::Request { version, .. } | Self::Response { version, .. } => *version,
+		}
+	}
+
+	#[must_use]
+	pub fn into_owned(self) -> Head<'static> {
+		match self {
+			Self::Request {
+				method,
+				target,
+				version,
+				headers,
+			} => Head::Request {
+				method,
+				target: Cow::Owned(target.into_owned()),
+				version,
+				headers: headers.into_owned(),
+			},
+			Self::Response {
+				version,
+				status,
+				reason,
+				headers,
+			} => Head::Response {
+				version,
+				status,
+				reason: Cow::Owned(reason.into_owned()),
+				headers: headers.into_owned(),
+			},
+		}
+	}
+}
+
+/// What the [`Parser`] found in the bytes it was given.
+#[derive(Debug)]
+pub enum Event<'i> {
+	/// All of the bytes were used, and more are needed to continue.
+	NeedMore,
+	/// The head of the message was parsed.
+	Head(Head<'static>),
+	/// A part of the body, borrowed from the bytes that were pushed.
+	BodyChunk(&'i [u8]),
+	/// The message is complete.
+	Done,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+	Request,
+	Response,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+	Head,
+	Length(usize),
+	ChunkSize,
+	ChunkData(usize),
+	/// The line break after the data of a chunk.
+	ChunkEnd,
+	Trailers,
+	/// The body of a response without a length, which ends when the connection closes.
+	UntilClose,
+	Done,
+}
+
+/// An incremental HTTP/1.0 and HTTP/1.1 parser that does no I/O of its own.
+///
+/// Bytes are pushed as they arrive, and each push reports how many of them
+/// were used along with an [`Event`]. Unused bytes belong to the rest of the
+/// message, or to the next message once it is done, and should be pushed
+/// again. Only incomplete lines of the head and of chunked framing are
+/// buffered, so the body is never copied.
+#[derive(Debug)]
+pub struct Parser {
+	kind: Kind,
+	config: ParseConfig,
+	state: State,
+	/// The method of the request being responded to, which decides whether a response has a body.
+	method: Option<Method>,
+	has_body: bool,
+	buf: Vec<u8>,
+	body_len: usize,
+	trailers_len: usize,
+}
+
+impl Parser {
+	/// Creates a parser for a request.
+	#[must_use]
+	pub fn request(config: ParseConfig) -> Self {
+		Self::new(Kind::Request, config)
+	}
+
+	/// Creates a parser for a response to a request with an unknown method.
+	#[must_use]
+	pub fn response(config: ParseConfig) -> Self {
+		Self::new(Kind::Response, config)
+	}
+
+	/// Creates a parser for a response to a request with the given method.
+	///
+	/// A response to `HEAD` never has a body, even with a `content-length`,
+	/// and neither does a successful response to `CONNECT`.
+	#[must_use]
+	pub fn response_to(method: &Method, config: ParseConfig) -> Self {
+		Self {
+			method: Some(method.clone()),
+			..Self::new(Kind::Response, config)
+		}
+	}
+
+	fn new(kind: Kind, config: ParseConfig) -> Self {
+		Self {
+			kind,
+			config,
+			state: State::Head,
+			method: None,
+			has_body: false,
+			buf: Vec::new(),
+			body_len: 0,
+			trailers_len: 0,
+		}
+	}
+
+	/// Returns `true` once the whole message has been parsed.
+	#[must_use]
+	pub fn is_done(&self) -> bool {
+		self.state == State::Done
+	}
+
+	/// Returns `true` if the parsed head is followed by a body, even an empty one.
+	#[must_use]
+	pub fn has_body(&self) -> bool {
+		self.has_body
+	}
+
+	/// Tells the parser that the peer closed the connection.
+	///
+	/// # Errors
+	/// - If the message is incomplete, unless the body is delimited by the
+	///   connection closing.
+	pub fn eof(&mut self) -> Result<(), Error> {
+		match self.state {
+			State::UntilClose | State::Done => {
+				self.state = State::Done;
+
+				Ok(())
+			}
+			_ => Err(io::Error::from(io::ErrorKind::UnexpectedEof).into()),
+		}
+	}
+
+	/// Parses as much of the input as possible, returning the number of
+	/// bytes used and what was found.
+	///
+	/// # Errors
+	/// - If the message is not valid HTTP/1.0 or HTTP/1.1.
+	/// - If the message exceeds any of the limits in the [`ParseConfig`].
+	pub fn push<'i>(&mut self, input: &'i [u8]) -> Result<(usize, Event<'i>), Error> {
+		let mut used = 0;
+
+		// a line of chunked framing is not an event, so parsing continues until one is found
+		loop {
+			let rest = &input[used..];
+			let (n, event) = match self.state {
+				State::Done => (0, Event::Done),
+				State::Head => self.push_head(rest)?,
+				_ if rest.is_empty() => (0, Event::NeedMore),
+				State::Length(remaining) => {
+					let n = remaining.min(rest.len());
+
+					self.state = if n == remaining {
+						State::Done
+					} else {
+						State::Length(remaining - n)
+					};
+
+					(n, Event::BodyChunk(&rest[..n]))
+				}
+				State::ChunkData(remaining) => {
+					let n = remaining.min(rest.len());
+
+					self.state = if n == remaining {
+						State::ChunkEnd
+					} else {
+						State::ChunkData(remaining - n)
+					};
+
+					(n, Event::BodyChunk(&rest[..n]))
+				}
+				State::UntilClose => {
+					self.add_body(rest.len())?;
+
+					(rest.len(), Event::BodyChunk(rest))
+				}
+				State::ChunkSize | State::ChunkEnd | State::Trailers => {
+					let Some((n, line)) = self.line(rest)? else {
+						return Ok((input.len(), Event::NeedMore));
+					};
+
+					self.framing_line(&line)?;
+					used += n;
+
+					continue;
+				}
+			};
+
+			return Ok((used + n, event));
+		}
+	}
+
+	fn add_body(&mut self, len: usize) -> Result<(), Error> {
+		self.body_len = self.body_len.saturating_add(len);
+		self.config.check_body(self.body_len)
+	}
+
+	/// Handles a complete line of chunked framing.
+	fn framing_line(&mut self, line: &[u8]) -> Result<(), Error> {
+		match self.state {
+			State::ChunkSize => {
+				// chunk extensions are allowed after a semicolon, but have no meaning here
+				let size = line.split(|&b| b == b';').next().unwrap_or_default();
+
+				// only hex digits, since from_str_radix would also accept a sign
+				if size.is_empty() || !size.iter().all(u8::is_ascii_hexdigit) {
+					return Err(Error::InvalidFormat);
+				}
+
+				let size = std::str::from_utf8(size).map_err(|_| Error::InvalidFormat)?;
+				let size = usize::from_str_radix(size, 16).map_err(|_| Error::InvalidFormat)?;
+
+				if size == 0 {
+					self.state = State::Trailers;
+				} else {
+					self.add_body(size)?;
+					self.state = State::ChunkData(size);
+				}
+			}
+			State::ChunkEnd if line.is_empty() => self.state = State::ChunkSize,
+			State::Trailers if line.is_empty() => self.state = State::Done,
+			State::Trailers => {
+				// trailer fields are not merged into the headers, but still count towards the limit
+				self.trailers_len += line.len() + 2;
+
+				if self.trailers_len > self.config.max_headers_size {
+					return Err(Error::HeadersTooLarge);
+				}
+			}
+			_ => return Err(Error::InvalidFormat),
+		}
+
+		Ok(())
+	}
+
+	/// Returns the next line without its \r\n, buffering it if it is incomplete.
+	fn line<'i>(&mut self, input: &'i [u8]) -> Result<Option<Line<'i>>, Error> {
+		let limit = self.config.max_header_size;
+
+		if self.buf.is_empty() {
+			if let Some(end) = find(input, b"\r\n") {
+				if end > limit {
+					return Err(Error::HeaderTooLarge);
+				}
+
+				return Ok(Some((end + 2, Cow::Borrowed(&input[..end]))));
+			}
+		}
+
+		// the \r may have been at the end of the previous input
+		let start = self.buf.len().saturating_sub(1);
+
+		self.buf.extend_from_slice(input);
+
+		let Some(end) = find(&self.buf[start..], b"\r\n").map(|i| start + i) else {
+			if self.buf.len() > limit + 1 {
+				return Err(Error::HeaderTooLarge);
+			}
+
+			return Ok(None);
+		};
+
+		if end > limit {
+			return Err(Error::HeaderTooLarge);
+		}
+
+		let used = end + 2 - (self.buf.len() - input.len());
+		let mut line = mem::take(&mut self.buf);
+
+		line.truncate(end);
+
+		Ok(Some((used, Cow::Owned(line))))
+	}
+
+	fn push_head<'i>(&mut self, input: &'i [u8]) -> Result<(usize, Event<'i>), Error> {
+		let previous = self.buf.len();
+		let start = previous.saturating_sub(3);
+
+		self.buf.extend_from_slice(input);
+
+		let Some(end) = find(&self.buf[start..], b"\r\n\r\n").map(|i| start + i + 4) else {
+			self.check_partial_head(&self.buf)?;
+
+			return Ok((input.len(), Event::NeedMore));
+		};
+
+		let buf = mem::take(&mut self.buf);
+		let head = self.parse_head(&buf[..end])?.into_owned();
+
+		Ok((end - previous, Event::Head(head)))
+	}
+
+	/// Checks the limits of a head that is not complete yet, so it cannot
+	/// grow without bound.
+	fn check_partial_head(&self, buf: &[u8]) -> Result<(), Error> {
+		let Some(start_line) = find(buf, b"\r\n") else {
+			if buf.len() > self.config.max_start_line {
+				return Err(Error::StartLineTooLong);
+			}
+
+			return Ok(());
+		};
+
+		let fields = &buf[start_line + 2..];
+		let last_line = fields
+			.windows(2)
+			.rposition(|w| w == b"\r\n")
+			.map_or(fields, |i| &fields[i + 2..]);
+
+		if last_line.len() > self.config.max_header_size {
+			return Err(Error::HeaderTooLarge);
+		}
+
+		if fields.len() > self.config.max_headers_size {
+			return Err(Error::HeadersTooLarge);
+		}
+
+		Ok(())
+	}
+
+	/// Returns the length of the head at the start of the buffer, including
+	/// the empty line that ends it, or `None` if it is not complete yet.
+	///
+	/// Together with [`Parser::parse_head`], this lets a caller read the head
+	/// into its own buffer and parse it without copying.
+	///
+	/// # Errors
+	/// - If the incomplete head already exceeds the limits in the [`ParseConfig`].
+	pub fn head_len(&self, buf: &[u8]) -> Result<Option<usize>, Error> {
+		match find(buf, b"\r\n\r\n") {
+			Some(i) => Ok(Some(i + 4)),
+			None => self.check_partial_head(buf).map(|()| None),
+		}
+	}
+
+	/// Parses a complete head, as measured by [`Parser::head_len`], with the
+	/// target, reason phrase, header names and values borrowing from it.
+	///
+	/// The body, if any, is then parsed with [`Parser::push`].
+	///
+	/// # Errors
+	/// - If the head was already parsed, or does not end with an empty line.
+	/// - If the message is not valid HTTP/1.0 or HTTP/1.1.
+	/// - If the head exceeds any of the limits in the [`ParseConfig`].
+	pub fn parse_head<'b>(&mut self, head: &'b [u8]) -> Result<Head<'b>, Error> {
+		let head = head
+			.strip_suffix(b"\r\n\r\n")
+			.filter(|_| self.state == State::Head)
+			.ok_or(Error::InvalidFormat)?;
+
+		// every line ends with \r\n, so a lone \n is rejected rather than read as a line
+		// break, as is a \r that would be taken for the end of the last line
+		let bare_lf =
+			head.first() == Some(&b'\n') || head.windows(2).any(|w| w[1] == b'\n' && w[0] != b'\r');
+
+		if bare_lf || head.last() == Some(&b'\r') {
+			return Err(Error::InvalidFormat);
+		}
+
+		let mut lines = head
+			.split(|&b| b == b'\n')
+			.map(|line| line.strip_suffix(b"\r").unwrap_or(line));
+
+		let start_line = lines.next().unwrap_or_default();
+
+		if start_line.len() > self.config.max_start_line {
+			return Err(Error::StartLineTooLong);
+		}
+
+		let mut fields = FieldParser::new(&self.config);
+
+		for line in lines {
+			fields.line(Cow::Borrowed(line))?;
+		}
+
+		let (headers, content_length) = fields.finish()?;
+
+		let head = match self.kind {
+			Kind::Request => {
+				let mut parts = start_line.split(|&b| b == b' ');
+				let (Some(method), Some(target), Some(version), None) =
+					(parts.next(), parts.next(), parts.next(), parts.next())
+				else {
+					return Err(Error::InvalidFormat);
+				};
+
+				Head::Request {
+					method: Method::from_bytes(method)?,
+					target: Cow::Borrowed(std::str::from_utf8(target)?),
+					version: Version::from_bytes(version)?,
+					headers,
+				}
+			}
+			Kind::Response => {
+				let (version, rest) = start_line
+					.iter()
+					.position(|&b| b == b' ')
+					.map(|i| (&start_line[..i], &start_line[i + 1..]))
+					.ok_or(Error::UnsupportedHttp)?;
+				let version = Version::from_bytes(version)?;
+
+				// the reason phrase is optional, so the status code may be the end of the line
+				let (status, reason) = match rest.iter().position(|&b| b == b' ') {
+					Some(i) => (&rest[..i], &rest[i + 1..]),
+					None => (rest, &b""[..]),
+				};
+
+				Head::Response {
+					version,
+					status: std::str::from_utf8(status)?.parse()?,
+					reason: String::from_utf8_lossy(reason),
+					headers,
+				}
+			}
+		};
+
+		self.state = self.body_state(&head, content_length)?;
+		// an empty body is still a body, unlike one that is forbidden or has no framing at all
+		self.has_body =
+			self.state != State::Done || (content_length == Some(0) && !self.forbids_body(&head));
+
+		Ok(head)
+	}
+
+	/// Returns `true` if a response cannot have a body, regardless of its headers.
+	fn forbids_body(&self, head: &Head<'_>) -> bool {
+		let Head::Response { status, .. } = head else {
+			return false;
+		};
+
+		let status = *status;
+
+		match self.method {
+			Some(Method::Head) => true,
+			// the connection becomes a tunnel, so anything after the head belongs to it
+			Some(Method::Connect) if status.is_success() => true,
+			_ => {
+				status.is_informational()
+					|| status == StatusCode::NO_CONTENT
+					|| status == StatusCode::NOT_MODIFIED
+			}
+		}
+	}
+
+	/// Determines how the body is delimited, following RFC 9112 section 6.3.
+	fn body_state(&self, head: &Head<'_>, content_length: Option<usize>) -> Result<State, Error> {
+		if self.forbids_body(head) {
+			return Ok(State::Done);
+		}
+
+		let headers = head.headers();
+
+		if headers.contains(header::TRANSFER_ENCODING) {
+			// HTTP/1.0 has no transfer codings, so the framing cannot be trusted
+			if head.version() == Version::Http10 {
+				return Err(Error::InvalidFormat);
+			}
+
+			let chunked = headers
+				.get_all(header::TRANSFER_ENCODING)
+				.flat_map(|value| value.split(','))
+				.last()
+				.is_some_and(|coding| coding.trim().eq_ignore_ascii_case("chunked"));
+
+			return match (chunked, self.kind) {
+				(true, _) => Ok(State::ChunkSize),
+				// a request body must have a known length
+				(false, Kind::Request) => Err(Error::InvalidFormat),
+				(false, Kind::Response) => Ok(State::UntilClose),
+			};
+		}
+
+		match (content_length, self.kind) {
+			(Some(0), _) | (None, Kind::Request) => Ok(State::Done),
+			(Some(length), _) => {
+				self.config.check_body(length)?;
+
+				Ok(State::Length(length))
+			}
+			(None, Kind::Response) => Ok(State::UntilClose),
+		}
+	}
+}
+
+/// Reads a single message from the reader, without reading past its end.
+///
+/// The body is `None` if the message has no body framing at all.
+pub(crate) fn read_message<R>(
+	reader: &mut R,
+	mut parser: Parser,
+) -> Result<(Head<'static>, Option<Vec<u8>>), Error>
+where
+	R: BufRead,
+{
+	let head = loop {
+		let data = reader.fill_buf()?;
+
+		if data.is_empty() {
+			return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+		}
+
+		let (used, event) = parser.push(data)?;
+		let head = match event {
+			Event::Head(head) => Some(head),
+			_ => None,
+		};
+
+		reader.consume(used);
+
+		if let Some(head) = head {
+			break head;
+		}
+	};
+
+	let body = read_body(reader, &mut parser)?;
+
+	Ok((head, body))
+}
+
+/// Reads the rest of a message whose head was already parsed.
+pub(crate) fn read_body<R>(reader: &mut R, parser: &mut Parser) -> Result<Option<Vec<u8>>, Error>
+where
+	R: BufRead,
+{
+	let mut body = parser.has_body().then(Vec::new);
+
+	while !parser.is_done() {
+		let data = reader.fill_buf()?;
+
+		if data.is_empty() {
+			parser.eof()?;
+
+			break;
+		}
+
+		let (used, event) = parser.push(data)?;
+
+		if let Event::BodyChunk(chunk) = event {
+			body.get_or_insert_with(Vec::new).extend_from_slice(chunk);
+		}
+
+		reader.consume(used);
+	}
+
+	Ok(body)
+}
+
+/// Reads the body of a message whose head was already parsed as it is
+/// needed, rather than all at once, without reading past its end.
+pub(crate) struct BodyReader<R> {
+	reader: R,
+	parser: Parser,
+}
+
+impl<R: BufRead> BodyReader<R> {
+	pub(crate) fn new(reader: R, parser: Parser) -> Self {
+		Self { reader, parser }
+	}
+
+	fn read_body(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
+		while !self.parser.is_done() && !buf.is_empty() {
+			let data = self.reader.fill_buf()?;
+
+			if data.is_empty() {
+				self.parser.eof()?;
+
+				break;
+			}
+
+			// a chunk of the body is no longer than its input, so it always fits
+			let data = &data[..data.len().min(buf.len())];
+			let (used, event) = self.parser.push(data)?;
+			let n = match event {
+				Event::BodyChunk(chunk) => {
+					buf[..chunk.len()].copy_from_slice(chunk);
+					chunk.len()
+				}
+				_ => 0,
+			};
+
+			self.reader.consume(used);
+
+			if n > 0 {
+				return Ok(n);
+			}
+		}
+
+		Ok(0)
+	}
+}
+
+impl<R: BufRead> Read for BodyReader<R> {
+	fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+		self.read_body(buf).map_err(|error| match error {
+			Error::Io(e) => e,
+			error => io::Error::new(io::ErrorKind::InvalidData, error),
+		})
+	}
+}
+
+/// Reads a single message from the async reader, without reading past its end.
+///
+/// This is the async counterpart of [`read_message`], so both behave the same.
+#[cfg(feature = "async")]
+pub(crate) async fn read_message_async<R>(
+	reader: &mut R,
+	mut parser: Parser,
+) -> Result<(Head<'static>, Option<Vec<u8>>), Error>
+where
+	R: AsyncBufRead + Unpin,
+{
+	let head = loop {
+		let data = reader.fill_buf().await?;
+
+		if data.is_empty() {
+			return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+		}
+
+		let (used, event) = parser.push(data)?;
+		let head = match event {
+			Event::Head(head) => Some(head),
+			_ => None,
+		};
+
+		reader.consume(used);
+
+		if let Some(head) = head {
+			break head;
+		}
+	};
+
+	let body = read_body_async(reader, &mut parser).await?;
+
+	Ok((head, body))
+}
+
+/// Reads the rest of a message whose head was already parsed from the async reader.
+#[cfg(feature = "async")]
+pub(crate) async fn read_body_async<R>(
+	reader: &mut R,
+	parser: &mut Parser,
+) -> Result<Option<Vec<u8>>, Error>
+where
+	R: AsyncBufRead + Unpin,
+{
+	let mut body = parser.has_body().then(Vec::new);
+
+	while !parser.is_done() {
+		let data = reader.fill_buf().await?;
+
+		if data.is_empty() {
+			parser.eof()?;
+
+			break;
+		}
+
+		let (used, event) = parser.push(data)?;
+
+		if let Event::BodyChunk(chunk) = event {
+			body.get_or_insert_with(Vec::new).extend_from_slice(chunk);
+		}
+
+		reader.consume(used);
+	}
+
+	Ok(body)
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+	haystack
+		.windows(needle.len())
+		.position(|window| window == needle)
+}
@@ -0,0 +1,250 @@
+use std::{
+	io::{self, Read, Write},
+	net::{IpAddr, SocketAddr, ToSocketAddrs},
+};
+
+#[cfg(feature = "async")]
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use url::{Host, ParseError, Url};
+
+use crate::Error;
+
+const VERSION: u8 = 5;
+const AUTH_VERSION: u8 = 1;
+
+const NO_AUTH: u8 = 0;
+const USER_PASS: u8 = 2;
+
+const CONNECT: u8 = 1;
+const SUCCEEDED: u8 = 0;
+
+const IPV4: u8 = 1;
+const DOMAIN: u8 = 3;
+const IPV6: u8 = 4;
+
+/// The username and password sent to the proxy, if any.
+pub(crate) type Credentials<'a> = Option<(&'a str, &'a str)>;
+
+/// The destination that the proxy is asked to connect to.
+enum Target<'u> {
+	Domain(&'u str, u16),
+	Addr(SocketAddr),
+}
+
+impl<'u> Target<'u> {
+	fn from_url(url: &'u Url) -> Result<Self, Error> {
+		let port = url
+			.port_or_known_default()
+			.ok_or(Error::InvalidUrl(ParseError::InvalidPort))?;
+
+		Ok(match url.host() {
+			Some(Host::Domain(domain)) => Self::Domain(domain, port),
+			Some(Host::Ipv4(ip)) => Self::Addr((ip, port).into()),
+			Some(Host::Ipv6(ip)) => Self::Addr((ip, port).into()),
+			None => return Err(Error::InvalidUrl(ParseError::EmptyHost)),
+		})
+	}
+
+	fn connect_request(&self) -> Result<Vec<u8>, Error> {
+		let mut message = vec![VERSION, CONNECT, 0];
+
+		let port = match self {
+			Self::Domain(domain, port) => {
+				let len = u8::try_from(domain.len()).map_err(|_| Error::InvalidFormat)?;
+
+				message.extend([DOMAIN, len]);
+				message.extend(domain.as_bytes());
+				port
+			}
+			Self::Addr(addr) => {
+				match addr.ip() {
+					IpAddr::V4(ip) => {
+						message.push(IPV4);
+						message.extend(ip.octets());
+					}
+					IpAddr::V6(ip) => {
+						message.push(IPV6);
+						message.extend(ip.octets());
+					}
+				}
+
+				&addr.port()
+			}
+		};
+
+		message.extend(port.to_be_bytes());
+
+		Ok(message)
+	}
+}
+
+/// Opens a connection to the URL through a SOCKS5 proxy, on a stream that
+/// is connected to the proxy.
+///
+/// With `remote_dns`, the proxy resolves the host of the URL instead of
+/// the client.
+pub(crate) fn handshake<S>(
+	stream: &mut S,
+	url: &Url,
+	remote_dns: bool,
+	credentials: Credentials,
+) -> Result<(), Error>
+where
+	S: Read + Write,
+{
+	let mut target = Target::from_url(url)?;
+
+	if let Target::Domain(host, port) = target {
+		if !remote_dns {
+			target = Target::Addr(first((host, port).to_socket_addrs()?)?);
+		}
+	}
+
+	let mut reply = [0; 2];
+
+	stream.write_all(&greeting(credentials))?;
+	stream.read_exact(&mut reply)?;
+
+	if let Some((username, password)) = method(reply, credentials)? {
+		stream.write_all(&authentication(username, password)?)?;
+		stream.read_exact(&mut reply)?;
+		check_authentication(reply)?;
+	}
+
+	let mut head = [0; 5];
+
+	stream.write_all(&target.connect_request()?)?;
+	stream.read_exact(&mut head)?;
+
+	let mut rest = vec![0; remaining(head)?];
+
+	stream.read_exact(&mut rest)?;
+
+	Ok(())
+}
+
+/// Opens a connection to the URL through a SOCKS5 proxy without blocking.
+#[cfg(feature = "async")]
+pub(crate) async fn handshake_async<S>(
+	stream: &mut S,
+	url: &Url,
+	remote_dns: bool,
+	credentials: Credentials<'_>,
+) -> Result<(), Error>
+where
+	S: AsyncRead + AsyncWrite + Unpin,
+{
+	let mut target = Target::from_url(url)?;
+
+	if let Target::Domain(host, port) = target {
+		if !remote_dns {
+			target = Target::Addr(first(tokio::net::lookup_host((host, port)).await?)?);
+		}
+	}
+
+	let mut reply = [0; 2];
+
+	stream.write_all(&greeting(credentials)).await?;
+	stream.read_exact(&mut reply).await?;
+
+	if let Some((username, password)) = method(reply, credentials)? {
+		stream
+			.write_all(&authentication(username, password)?)
+			.await?;
+		stream.read_exact(&mut reply).await?;
+		check_authentication(reply)?;
+	}
+
+	let mut head = [0; 5];
+
+	stream.write_all(&target.connect_request()?).await?;
+	stream.read_exact(&mut head).await?;
+
+	let mut rest = vec![0; remaining(head)?];
+
+	stream.read_exact(&mut rest).await?;
+
+	Ok(())
+}
+
+/// Returns a description of a reply code sent by the proxy.
+pub(crate) fn reply_message(code: u8) -> &'static str {
+	match code {
+		1 => "general failure",
+		2 => "connection not allowed by ruleset",
+		3 => "network unreachable",
+		4 => "host unreachable",
+		5 => "connection refused",
+		6 => "TTL expired",
+		7 => "command not supported",
+		8 => "address type not supported",
+		_ => "unknown error",
+	}
+}
+
+fn first<I>(mut addrs: I) -> io::Result<SocketAddr>
+where
+	I: Iterator<Item = SocketAddr>,
+{
+	addrs
+		.next()
+		.ok_or_else(|| io::Error::from(io::ErrorKind::NotFound))
+}
+
+fn greeting(credentials: Credentials) -> Vec<u8> {
+	match credentials {
+		Some(_) => vec![VERSION, 2, NO_AUTH, USER_PASS],
+		None => vec![VERSION, 1, NO_AUTH],
+	}
+}
+
+/// Returns the credentials to send if the proxy chose to authenticate.
+fn method(reply: [u8; 2], credentials: Credentials) -> Result<Credentials, Error> {
+	match reply {
+		[VERSION, NO_AUTH] => Ok(None),
+		[VERSION, USER_PASS] if credentials.is_some() => Ok(credentials),
+		[VERSION, _] => Err(Error::SocksAuth),
+		_ => Err(Error::InvalidFormat),
+	}
+}
+
+fn authentication(username: &str, password: &str) -> Result<Vec<u8>, Error> {
+	let username_len = u8::try_from(username.len()).map_err(|_| Error::SocksAuth)?;
+	let password_len = u8::try_from(password.len()).map_err(|_| Error::SocksAuth)?;
+	let mut message = vec![AUTH_VERSION, username_len];
+
+	message.extend(username.as_bytes());
+	message.push(password_len);
+	message.extend(password.as_bytes());
+
+	Ok(message)
+}
+
+fn check_authentication(reply: [u8; 2]) -> Result<(), Error> {
+	match reply {
+		[AUTH_VERSION, SUCCEEDED] => Ok(()),
+		[AUTH_VERSION, _] => Err(Error::SocksAuth),
+		_ => Err(Error::InvalidFormat),
+	}
+}
+
+/// Returns the number of bytes left in a reply after its first five, which
+/// are the bound address and port that the client has no use for.
+fn remaining(head: [u8; 5]) -> Result<usize, Error> {
+	let [version, code, _, kind, first] = head;
+
+	if version != VERSION {
+		return Err(Error::InvalidFormat);
+	}
+
+	if code != SUCCEEDED {
+		return Err(Error::Socks(code));
+	}
+
+	Ok(match kind {
+		IPV4 => 3 + 2,
+		IPV6 => 15 + 2,
+		DOMAIN => usize::from(first) + 2,
+		_ => return Err(Error::InvalidFormat),
+	})
+}
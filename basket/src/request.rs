@@ -1,63 +1,139 @@
+use std::fmt;
 use std::io::{self, Read, Write};
-use std::net::TcpStream;
-
-#[cfg(any(feature = "json", feature = "xml"))]
+#[cfg(all(unix, feature = "async"))]
+use std::path::Path;
+#[cfg(unix)]
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::{Mutex, PoisonError};
+
+#[cfg(any(feature = "form", feature = "json", feature = "xml"))]
 use serde::{de::DeserializeOwned, Serialize};
-use url::{ParseError, Url};
-
-use crate::{extract, header};
-use crate::{Error, IntoHeader};
+use url::{ParseError, Position, Url};
+
+#[cfg(feature = "cookies")]
+use crate::cookie::Cookies;
+#[cfg(feature = "multipart")]
+use crate::multipart::{self, Form, Multipart};
+use crate::parse::{self, Head, Parser};
+use crate::{header, HeaderMap};
+use crate::{
+	Client, Connector, Error, Extensions, IntoHeader, ParseConfig, Proxy, RetryPolicy,
+	TcpConnector, Version,
+};
 
 use super::header::Header;
 use super::response::Response;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Method {
+	Connect,
 	Delete,
 	Get,
+	Head,
 	Options,
 	Patch,
 	Post,
 	Put,
+	Trace,
+	/// Any other method, such as `PROPFIND` or `PURGE`. A request with a
+	/// method that is not a valid token fails when it is sent.
+	Extension(String),
 }
 
 impl Method {
 	#[must_use]
 	pub fn as_str(&self) -> &str {
 		match self {
+			Self::Connect => "CONNECT",
 			Self::Delete => "DELETE",
 			Self::Get => "GET",
+			Self::Head => "HEAD",
 			Self::Options => "OPTIONS",
 			Self::Patch => "PATCH",
 			Self::Post => "POST",
 			Self::Put => "PUT",
+			Self::Trace => "TRACE",
+			Self::Extension(method) => method,
 		}
 	}
 
-	/// Converts a byte slice to a Method.
+	/// Returns `true` if sending the request more than once has the same
+	/// effect as sending it once, so that it is safe to retry.
+	///
+	/// Extension methods are never assumed to be idempotent.
+	#[must_use]
+	pub fn is_idempotent(&self) -> bool {
+		matches!(
+			self,
+			Self::Delete | Self::Get | Self::Head | Self::Options | Self::Put | Self::Trace
+		)
+	}
+
+	/// Converts a byte slice to a Method. Methods are case-sensitive, so
+	/// anything other than the standard names is an extension method.
 	///
 	/// # Errors
-	/// - If the byte slice is not a valid HTTP method.
+	/// - If the byte slice is not a valid token.
 	pub fn from_bytes(value: &[u8]) -> Result<Method, Error> {
 		Ok(match value {
+			b"CONNECT" => Self::Connect,
 			b"DELETE" => Self::Delete,
 			b"GET" => Self::Get,
+			b"HEAD" => Self::Head,
 			b"OPTIONS" => Self::Options,
 			b"PATCH" => Self::Patch,
 			b"POST" => Self::Post,
 			b"PUT" => Self::Put,
-			_ => return Err(Error::UnknownMethod),
+			b"TRACE" => Self::Trace,
+			_ => match std::str::from_utf8(value) {
+				Ok(method) if header::is_valid_name(method) => Self::Extension(method.to_string()),
+				_ => return Err(Error::UnknownMethod),
+			},
 		})
 	}
 }
 
+impl FromStr for Method {
+	type Err = Error;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		Self::from_bytes(s.as_bytes())
+	}
+}
+
+impl fmt::Display for Method {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(self.as_str())
+	}
+}
+
+/// A body that is read while the request is being written, rather than
+/// being held in memory.
+pub(crate) struct Stream {
+	reader: Box<dyn Read + Send>,
+	length: Option<u64>,
+}
+
+impl fmt::Debug for Stream {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("Stream")
+			.field("length", &self.length)
+			.finish_non_exhaustive()
+	}
+}
+
 #[must_use]
 #[derive(Debug)]
 pub struct Request<'h> {
 	pub url: Url,
 	pub method: Method,
 	pub body: Option<Vec<u8>>,
-	pub headers: Vec<Header<'h>>,
+	pub version: Version,
+	pub headers: HeaderMap<'h>,
+	pub extensions: Extensions,
+	/// Behind a lock so that sending, which consumes it, only needs a shared reference.
+	stream: Mutex<Option<Stream>>,
 }
 
 impl<'h> Request<'h> {
@@ -69,6 +145,10 @@ impl<'h> Request<'h> {
 		RequestBuilder::new(Method::Get, url)
 	}
 
+	pub fn head<U: TryInto<Url, Error = ParseError>>(url: U) -> RequestBuilder<'h> {
+		RequestBuilder::new(Method::Head, url)
+	}
+
 	pub fn options<U: TryInto<Url, Error = ParseError>>(url: U) -> RequestBuilder<'h> {
 		RequestBuilder::new(Method::Options, url)
 	}
@@ -85,89 +165,317 @@ impl<'h> Request<'h> {
 		RequestBuilder::new(Method::Put, url)
 	}
 
-	/// Parses a request from a reader.
+	/// Parses a request from a reader, rejecting folded headers.
+	///
+	/// The reader is buffered while parsing, so it may be read past the end
+	/// of the request.
 	///
 	/// # Errors
 	/// - If the method is not a valid HTTP method.
 	/// - If the URL is not a valid URL.
-	/// - If the HTTP version is not HTTP/1.1.
+	/// - If the HTTP version is not HTTP/1.0 or HTTP/1.1.
 	/// - If the headers are not valid.
 	/// - If the content length is not a valid integer.
 	pub fn from_reader<R>(reader: &mut R) -> Result<Self, Error>
 	where
 		R: Read,
 	{
-		let method = extract::until(reader, b" ")?;
-		let method = Method::from_bytes(&method)?;
-		let path = extract::until(reader, b" ")?;
+		Self::from_reader_with(reader, &ParseConfig::default())
+	}
 
-		extract::http_version(reader)?;
-		extract::skip(reader, b"\r\n")?;
+	/// Parses a request from a reader with the given options.
+	///
+	/// # Errors
+	/// - If the method is not a valid HTTP method.
+	/// - If the URL is not a valid URL.
+	/// - If the HTTP version is not HTTP/1.0 or HTTP/1.1.
+	/// - If the headers are not valid.
+	/// - If the content length is not a valid integer.
+	/// - If the request exceeds any of the limits in the [`ParseConfig`].
+	pub fn from_reader_with<R>(reader: &mut R, config: &ParseConfig) -> Result<Self, Error>
+	where
+		R: Read,
+	{
+		let (head, body) = parse::read_message(
+			&mut io::BufReader::new(reader),
+			Parser::request(config.clone()),
+		)?;
 
-		let url = Url::parse(&format!("data:{}", String::from_utf8(path)?))?;
-		let (headers, content_length) = header::from_reader(reader)?;
+		Self::from_head(head, body)
+	}
 
-		let mut request = Self {
-			headers,
+	/// Creates a request from a head parsed with [`Parser::parse_head`],
+	/// keeping any headers that borrow from its buffer.
+	///
+	/// # Errors
+	/// - If the head is not a request head.
+	/// - If the target is not a valid URL path.
+	pub fn from_head(head: Head<'h>, body: Option<Vec<u8>>) -> Result<Self, Error> {
+		let Head::Request {
 			method,
-			url,
-			body: None,
+			target,
+			version,
+			headers,
+		} = head
+		else {
+			return Err(Error::InvalidFormat);
 		};
 
-		if let Some(content_length) = content_length {
-			let mut body = Vec::with_capacity(content_length);
-
-			reader.take(content_length as u64).read_to_end(&mut body)?;
-
-			request.body = Some(body);
-		}
-
-		Ok(request)
+		Ok(Self {
+			url: Url::parse(&format!("data:{target}"))?,
+			method,
+			body,
+			version,
+			headers,
+			extensions: Extensions::new(),
+			stream: Mutex::new(None),
+		})
 	}
 
 	/// Sends the request and returns the response.
 	///
+	/// A body set with [`RequestBuilder::body_reader`] is consumed, so it is
+	/// not sent again if the request is sent a second time.
+	///
 	/// # Errors
 	/// - If the request could not be sent.
 	/// - If the response could not be read.
 	pub fn send(&self) -> Result<Response<'h>, Error> {
-		let mut stream = TcpStream::connect(self.url.socket_addrs(|| None)?.as_slice())?;
+		self.send_with(&ParseConfig::default().obs_fold(true), &TcpConnector, None)
+	}
+
+	/// Sends the request over a connection opened by the connector, through
+	/// the proxy if any, and parses the response with the given options.
+	pub(crate) fn send_with(
+		&self,
+		config: &ParseConfig,
+		connector: &dyn Connector,
+		proxy: Option<&Proxy>,
+	) -> Result<Response<'h>, Error> {
+		let stream = match proxy {
+			Some(proxy) => proxy.connect(connector, &self.url)?,
+			None => connector.connect(&self.url)?,
+		};
+
+		self.exchange(stream, config, proxy)
+	}
 
-		self.write(&mut stream)?;
+	/// Writes the request to a connected stream and reads the response.
+	fn exchange<S>(
+		&self,
+		mut stream: S,
+		config: &ParseConfig,
+		proxy: Option<&Proxy>,
+	) -> Result<Response<'h>, Error>
+	where
+		S: Read + Write,
+	{
+		self.write(&mut stream, proxy)?;
 		stream.flush()?;
 
-		// Response::from_reader reads a lot of tiny chunks from the stream
-		// so we wrap it in a BufReader to reduce the overhead
 		let mut reader = io::BufReader::new(stream);
+		let (head, body) = parse::read_message(
+			&mut reader,
+			Parser::response_to(&self.method, config.clone()),
+		)?;
+		let mut response = Response::from_head(head, body)?;
+
+		response.url = Some(self.url.clone());
 
-		Response::from_reader(&mut reader)
+		Ok(response)
+	}
+
+	/// Sends the request without blocking and returns the response.
+	///
+	/// A body set with [`RequestBuilder::body_reader`] is read into memory
+	/// before it is sent, since the reader itself is blocking.
+	///
+	/// # Errors
+	/// - If the request could not be sent.
+	/// - If the response could not be read.
+	#[cfg(feature = "async")]
+	pub async fn send_async(&self) -> Result<Response<'h>, Error> {
+		self.send_async_with(&ParseConfig::default().obs_fold(true), None)
+			.await
 	}
 
-	fn write<W>(&self, write: &mut W) -> io::Result<()>
+	/// Sends the request without blocking through the proxy, if any, and
+	/// parses the response with the given options.
+	#[cfg(feature = "async")]
+	pub(crate) async fn send_async_with(
+		&self,
+		config: &ParseConfig,
+		proxy: Option<&Proxy>,
+	) -> Result<Response<'h>, Error> {
+		if let Some(proxy) = proxy {
+			let stream = proxy.connect_async(&self.url).await?;
+
+			self.exchange_async(stream, config, Some(proxy)).await
+		} else {
+			let stream = connect_async(&self.url).await?;
+
+			self.exchange_async(stream, config, None).await
+		}
+	}
+
+	/// Sends the request to the Unix socket at the path without blocking and
+	/// parses the response with the given options.
+	#[cfg(all(unix, feature = "async"))]
+	pub(crate) async fn send_unix_async(
+		&self,
+		path: &Path,
+		config: &ParseConfig,
+	) -> Result<Response<'h>, Error> {
+		let stream = tokio::net::UnixStream::connect(path).await?;
+
+		self.exchange_async(stream, config, None).await
+	}
+
+	/// Writes the request to a connected stream and reads the response without blocking.
+	#[cfg(feature = "async")]
+	async fn exchange_async<S>(
+		&self,
+		mut stream: S,
+		config: &ParseConfig,
+		proxy: Option<&Proxy>,
+	) -> Result<Response<'h>, Error>
+	where
+		S: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+	{
+		use tokio::io::AsyncWriteExt;
+
+		let mut message = Vec::new();
+
+		self.write(&mut message, proxy)?;
+		stream.write_all(&message).await?;
+		stream.flush().await?;
+
+		let mut reader = tokio::io::BufReader::new(stream);
+		let (head, body) = parse::read_message_async(
+			&mut reader,
+			Parser::response_to(&self.method, config.clone()),
+		)
+		.await?;
+		let mut response = Response::from_head(head, body)?;
+
+		response.url = Some(self.url.clone());
+
+		Ok(response)
+	}
+
+	/// Writes the request, in the form that a forwarding proxy expects if
+	/// there is one.
+	fn write<W>(&self, write: &mut W, proxy: Option<&Proxy>) -> io::Result<()>
 	where
 		W: Write,
 	{
-		write!(write, "{} {}", self.method.as_str(), self.url.path())?;
+		// an extension method could otherwise end the request line early
+		if !header::is_valid_name(self.method.as_str()) {
+			return Err(io::Error::new(
+				io::ErrorKind::InvalidInput,
+				Error::UnknownMethod,
+			));
+		}
+
+		// every header is checked first, so an invalid one leaves nothing
+		// half-written and the body still in place
+		self.headers
+			.validate()
+			.map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+
+		// a tunnel reaches the origin itself, so the request is written as if there was no proxy
+		let proxy = proxy.filter(|proxy| !proxy.tunnels(&self.url));
+
+		if self.method == Method::Connect {
+			// CONNECT uses the authority form, since it asks for a tunnel rather than a resource
+			let host = self.url.host_str().unwrap_or_default();
+			let port = self.url.port_or_known_default().unwrap_or_default();
+
+			write!(write, "CONNECT {host}:{port}")?;
+		} else if proxy.is_some() {
+			// a proxy needs the absolute form to know where to forward the request,
+			// which must not include any credentials
+			write!(
+				write,
+				"{} {}{}",
+				self.method,
+				&self.url[..Position::BeforeUsername],
+				&self.url[Position::BeforeHost..Position::AfterQuery]
+			)?;
+		} else {
+			write!(write, "{} {}", self.method, self.url.path())?;
 
-		if let Some(query) = self.url.query() {
-			write.write_all(query.as_bytes())?;
+			if let Some(query) = self.url.query() {
+				write!(write, "?{query}")?;
+			}
 		}
 
-		write.write_all(b" HTTP/1.1\r\n")?;
+		write!(write, " {}\r\n", self.version)?;
+
+		let mut stream = self.take_stream();
+		let mut buffered = None;
+
+		// HTTP/1.0 has no chunked encoding, so a body of unknown length is read into memory to measure it
+		if self.version == Version::Http10 {
+			if let Some(mut stream) = stream.take_if(|stream| stream.length.is_none()) {
+				let mut body = Vec::new();
+
+				stream.reader.read_to_end(&mut body)?;
+				buffered = Some(body);
+			}
+		}
 
 		for header in &self.headers {
-			write!(write, "{}: {}\r\n", header.name, header.value)?;
+			if buffered.is_some() && header.name.eq_ignore_ascii_case(header::TRANSFER_ENCODING) {
+				continue;
+			}
+
+			write!(write, "{}: ", header.name)?;
+			write.write_all(header.value.as_bytes())?;
+			write.write_all(b"\r\n")?;
+		}
+
+		if let Some(body) = &buffered {
+			write!(write, "{}: {}\r\n", header::CONTENT_LENGTH, body.len())?;
+		}
+
+		if let Some(proxy) = proxy {
+			proxy.write_authorization(write)?;
 		}
 
 		write.write_all(b"\r\n")?;
 
-		if let Some(body) = &self.body {
+		if let Some(body) = self.body.as_ref().or(buffered.as_ref()) {
 			write.write_all(body.as_slice())?;
 		}
 
+		if let Some(mut stream) = stream {
+			if stream.length.is_some() {
+				io::copy(&mut stream.reader, write)?;
+			} else {
+				write_chunked(&mut stream.reader, write)?;
+			}
+		}
+
 		Ok(())
 	}
 
+	/// Takes the body reader, which can only be sent once.
+	fn take_stream(&self) -> Option<Stream> {
+		self.stream
+			.lock()
+			.unwrap_or_else(PoisonError::into_inner)
+			.take()
+	}
+
+	/// Returns `true` if the body is read from a reader, so it cannot be sent again.
+	pub(crate) fn has_stream(&self) -> bool {
+		self.stream
+			.lock()
+			.unwrap_or_else(PoisonError::into_inner)
+			.is_some()
+	}
+
 	/// Deserializes the body as JSON.
 	///
 	/// # Errors
@@ -198,6 +506,78 @@ impl<'h> Request<'h> {
 		Ok(quick_xml::de::from_str(content)?)
 	}
 
+	/// Deserializes the body as `application/x-www-form-urlencoded` data.
+	///
+	/// # Errors
+	/// - If the body is not present.
+	/// - Forwards any errors from [``urlencoded::from_bytes``](crate::urlencoded::from_bytes).
+	#[cfg(feature = "form")]
+	pub fn form<T: DeserializeOwned>(self) -> Result<T, Error> {
+		let Some(body) = self.body else {
+			return Err(Error::ExpectedBody);
+		};
+
+		crate::urlencoded::from_bytes(&body)
+	}
+
+	/// Returns a reader over the body, which is empty if there is none.
+	///
+	/// [`Router::listen`](crate::server::Router::listen) leaves the body of a
+	/// `multipart/*` request on the connection, so it is streamed from there
+	/// rather than from memory.
+	pub fn into_body_reader(self) -> Box<dyn Read + Send> {
+		match self
+			.stream
+			.into_inner()
+			.unwrap_or_else(PoisonError::into_inner)
+		{
+			Some(stream) => stream.reader,
+			None => Box::new(io::Cursor::new(self.body.unwrap_or_default())),
+		}
+	}
+
+	/// Sets a reader that the body is read from as it is needed.
+	pub(crate) fn set_body_reader<R>(&mut self, reader: R)
+	where
+		R: Read + Send + 'static,
+	{
+		self.body = None;
+		self.stream = Mutex::new(Some(Stream {
+			reader: Box::new(reader),
+			length: None,
+		}));
+	}
+
+	/// Returns a parser over the parts of a `multipart/form-data` body, which
+	/// reads the body as each part is read.
+	///
+	/// # Errors
+	/// - If the body is not present.
+	/// - If the content type is not multipart, or has no boundary.
+	#[cfg(feature = "multipart")]
+	pub fn multipart(self) -> Result<Multipart<Box<dyn Read + Send>>, Error> {
+		let boundary = self
+			.header(header::CONTENT_TYPE)
+			.and_then(multipart::boundary)
+			.ok_or(Error::InvalidMultipart)?
+			.to_string();
+
+		if self.body.is_none() && !self.has_stream() {
+			return Err(Error::ExpectedBody);
+		}
+
+		Ok(Multipart::new(self.into_body_reader(), &boundary))
+	}
+
+	/// Deserializes the query string, which is empty if the URL has no query.
+	///
+	/// # Errors
+	/// - Forwards any errors from [``urlencoded::from_str``](crate::urlencoded::from_str).
+	#[cfg(feature = "form")]
+	pub fn query<T: DeserializeOwned>(&self) -> Result<T, Error> {
+		crate::urlencoded::from_str(self.url.query().unwrap_or_default())
+	}
+
 	/// Returns the body as a string.
 	///
 	/// # Errors
@@ -218,6 +598,41 @@ impl<'h> Request<'h> {
 	pub fn bytes(self) -> Result<Vec<u8>, Error> {
 		self.body.ok_or(Error::ExpectedBody)
 	}
+
+	/// Returns the first value of a header.
+	#[must_use]
+	pub fn header(&self, name: &str) -> Option<&str> {
+		self.headers.get(name)
+	}
+
+	/// Returns `true` if the client expects the connection to stay open
+	/// after the response.
+	#[must_use]
+	pub fn keep_alive(&self) -> bool {
+		self.version.keep_alive(&self.headers)
+	}
+
+	/// Parses a header as a [`TypedHeader`](crate::TypedHeader).
+	#[must_use]
+	pub fn typed_get<H: crate::TypedHeader>(&self) -> Option<H> {
+		self.headers.typed_get()
+	}
+
+	/// Returns the cookies sent in the `cookie` header.
+	#[cfg(feature = "cookies")]
+	#[must_use]
+	pub fn cookies(&self) -> Cookies<'_> {
+		self.header(header::COOKIE)
+			.map(Cookies::parse)
+			.unwrap_or_default()
+	}
+
+	/// Returns the value of the cookie with the given name.
+	#[cfg(feature = "cookies")]
+	#[must_use]
+	pub fn cookie(&self, name: &str) -> Option<&str> {
+		self.cookies().get(name)
+	}
 }
 
 #[allow(clippy::module_name_repetitions)]
@@ -225,6 +640,7 @@ impl<'h> Request<'h> {
 #[derive(Debug)]
 pub struct RequestBuilder<'h> {
 	request: Request<'h>,
+	client: Client,
 	error: Option<Error>,
 }
 
@@ -235,13 +651,12 @@ impl<'h> RequestBuilder<'h> {
 	/// - If the URL is not a valid URL.
 	pub fn new<U: TryInto<Url, Error = ParseError>>(method: Method, url: U) -> Self {
 		let url = url.try_into().unwrap();
-		let mut headers = vec![Header {
-			name: "connection".into(),
-			value: "close".into(),
-		}];
+		let mut headers = HeaderMap::new();
+
+		headers.append(("connection", "close"));
 
 		if let Some(host) = url.host_str() {
-			headers.push(Header {
+			headers.append(Header {
 				name: "host".into(),
 				value: host.to_string().into(),
 			});
@@ -249,41 +664,166 @@ impl<'h> RequestBuilder<'h> {
 
 		Self {
 			error: None,
+			client: Client::default(),
 			request: Request {
 				method,
 				body: None,
+				version: Version::default(),
 				headers,
 				url,
+				extensions: Extensions::new(),
+				stream: Mutex::new(None),
 			},
 		}
 	}
 
+	/// Sets the HTTP version of the request. HTTP/1.1 is used by default.
+	pub fn version(mut self, version: Version) -> Self {
+		self.request.version = version;
+		self
+	}
+
+	/// Sets the client used to send the request.
+	pub fn client(mut self, client: Client) -> Self {
+		self.client = client;
+		self
+	}
+
+	/// Resends the request according to the policy if it fails with a
+	/// transient error, instead of using the client's policy.
+	pub fn retry(mut self, policy: RetryPolicy) -> Self {
+		self.client = self.client.retry(policy);
+		self
+	}
+
+	/// Sends the request to the Unix socket at the path, instead of the host
+	/// and port of the URL, as with [`Client::unix_socket`].
+	#[cfg(unix)]
+	pub fn unix_socket<P: Into<PathBuf>>(mut self, path: P) -> Self {
+		self.client = self.client.unix_socket(path);
+		self
+	}
+
+	/// Opens the connection for the request with the connector, instead of
+	/// the client's connector.
+	pub fn connector<C>(mut self, connector: C) -> Self
+	where
+		C: Connector + 'static,
+	{
+		self.client = self.client.connector(connector);
+		self
+	}
+
+	/// Sends the request through the proxy if none of the client's proxies
+	/// apply to the URL.
+	pub fn proxy(mut self, proxy: Proxy) -> Self {
+		self.client = self.client.proxy(proxy);
+		self
+	}
+
 	/// Sends the request and returns the response.
 	///
 	/// # Errors
 	/// - If the request could not be sent.
 	/// - If the response could not be read.
 	pub fn send(self) -> Result<Response<'h>, Error> {
+		let (client, request) = self.finish()?;
+
+		client.execute(request)
+	}
+
+	/// Sends the request without blocking and returns the response.
+	///
+	/// # Errors
+	/// - If a custom connector was set.
+	/// - If the request could not be sent.
+	/// - If the response could not be read.
+	#[cfg(feature = "async")]
+	pub async fn send_async(self) -> Result<Response<'h>, Error> {
+		let (client, request) = self.finish()?;
+
+		client.execute_async(request).await
+	}
+
+	fn finish(self) -> Result<(Client, Request<'h>), Error> {
 		if let Some(error) = self.error {
 			return Err(error);
 		}
 
-		if let Some(body) = self.request.body.as_ref() {
+		let builder = if let Some(body) = self.request.body.as_ref() {
 			let len = body.len();
 
 			self.header((header::CONTENT_LENGTH, len))
 		} else {
 			self
-		}
-		.request
-		.send()
+		};
+
+		Ok((builder.client, builder.request))
 	}
 
+	/// Sets a header, replacing any existing values with the same name.
+	///
+	/// An invalid header is not added, and its error is returned when the
+	/// request is sent.
 	pub fn header<H>(mut self, header: H) -> Self
 	where
 		H: IntoHeader<'h>,
 	{
-		self.request.headers.push(header.into_header());
+		let header = header.into_header();
+
+		match header.validate() {
+			Ok(()) => {
+				self.request.headers.insert(header);
+			}
+			Err(e) => {
+				self.error.get_or_insert(e);
+			}
+		}
+
+		self
+	}
+
+	/// Adds a header, keeping any existing values with the same name.
+	///
+	/// An invalid header is not added, and its error is returned when the
+	/// request is sent.
+	pub fn append_header<H>(mut self, header: H) -> Self
+	where
+		H: IntoHeader<'h>,
+	{
+		let header = header.into_header();
+
+		match header.validate() {
+			Ok(()) => self.request.headers.append(header),
+			Err(e) => {
+				self.error.get_or_insert(e);
+			}
+		}
+
+		self
+	}
+
+	/// Appends the fields of the value to the query string, with the same
+	/// encoding as [`RequestBuilder::form`].
+	#[cfg(feature = "form")]
+	pub fn query<T: Serialize>(mut self, query: &T) -> Self {
+		match crate::urlencoded::to_pairs(query) {
+			Ok(pairs) if pairs.is_empty() => {}
+			Ok(pairs) => {
+				self.request.url.query_pairs_mut().extend_pairs(pairs);
+			}
+			Err(e) => self.error = Some(e),
+		}
+
+		self
+	}
+
+	/// Appends a single pair to the query string, percent-encoding both parts.
+	pub fn query_pair<K: AsRef<str>, V: AsRef<str>>(mut self, key: K, value: V) -> Self {
+		self.request
+			.url
+			.query_pairs_mut()
+			.append_pair(key.as_ref(), value.as_ref());
 		self
 	}
 
@@ -298,7 +838,7 @@ impl<'h> RequestBuilder<'h> {
 			}
 		};
 
-		self.request.body = Some(bytes);
+		self.set_body(bytes);
 		self.header(header::CONTENT_TYPE_JSON)
 	}
 
@@ -313,14 +853,120 @@ impl<'h> RequestBuilder<'h> {
 			}
 		};
 
-		self.request.body = Some(bytes);
+		self.set_body(bytes);
 		self.header(header::CONTENT_TYPE_XML)
 	}
 
+	#[cfg(feature = "form")]
+	pub fn form<T: Serialize>(mut self, payload: &T) -> Self {
+		let bytes = match crate::urlencoded::to_string(payload) {
+			Ok(b) => b.into_bytes(),
+			Err(e) => {
+				self.error = Some(e);
+
+				return self;
+			}
+		};
+
+		self.set_body(bytes);
+		self.header(header::CONTENT_TYPE_FORM)
+	}
+
+	#[cfg(feature = "multipart")]
+	pub fn multipart(mut self, form: Form) -> Self {
+		if let Err(e) = form.validate() {
+			self.error.get_or_insert(e);
+
+			return self;
+		}
+
+		let content_type = form.content_type();
+
+		// a form held in memory is sent like any other body, so it can be retried
+		match form.into_bytes() {
+			Ok(bytes) => self.set_body(bytes),
+			Err(form) => {
+				let (reader, length) = form.into_reader();
+
+				self = self.body_reader(reader, length);
+			}
+		}
+
+		self.header((header::CONTENT_TYPE, content_type))
+	}
+
 	pub fn body<T: Into<Vec<u8>>>(mut self, payload: T) -> Self {
 		let bytes: Vec<u8> = payload.into();
 
-		self.request.body = Some(bytes);
+		self.set_body(bytes);
 		self.header(header::CONTENT_TYPE_PLAIN)
 	}
+
+	/// Streams the body from a reader while the request is sent.
+	///
+	/// If the length is not known, the body is sent with chunked
+	/// transfer encoding.
+	pub fn body_reader<R>(mut self, reader: R, length: Option<u64>) -> Self
+	where
+		R: Read + Send + 'static,
+	{
+		self.request.body = None;
+		self.request.stream = Mutex::new(Some(Stream {
+			reader: Box::new(reader),
+			length,
+		}));
+
+		if let Some(length) = length {
+			self.request.headers.remove(header::TRANSFER_ENCODING);
+			self.header((header::CONTENT_LENGTH, length.to_string()))
+		} else {
+			self.request.headers.remove(header::CONTENT_LENGTH);
+			self.header((header::TRANSFER_ENCODING, "chunked"))
+		}
+	}
+
+	/// Sets a body held in memory, replacing any reader set with
+	/// [`RequestBuilder::body_reader`] along with its chunked encoding.
+	fn set_body(&mut self, body: Vec<u8>) {
+		self.request.body = Some(body);
+		self.request.stream = Mutex::new(None);
+		self.request.headers.remove(header::TRANSFER_ENCODING);
+	}
+}
+
+/// Connects to the host and port of the URL without blocking.
+#[cfg(feature = "async")]
+pub(crate) async fn connect_async(url: &Url) -> Result<tokio::net::TcpStream, Error> {
+	let host = url
+		.host_str()
+		.ok_or(Error::InvalidUrl(ParseError::EmptyHost))?;
+	let port = url
+		.port_or_known_default()
+		.ok_or(Error::InvalidUrl(ParseError::InvalidPort))?;
+
+	Ok(tokio::net::TcpStream::connect((host, port)).await?)
+}
+
+/// Writes the reader to the writer using chunked transfer encoding.
+fn write_chunked<R, W>(reader: &mut R, write: &mut W) -> io::Result<()>
+where
+	R: Read + ?Sized,
+	W: Write,
+{
+	let mut buf = [0; 8192];
+
+	loop {
+		let n = match reader.read(&mut buf) {
+			Ok(0) => break,
+			Ok(n) => n,
+			Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+			Err(e) => return Err(e),
+		};
+
+		write!(write, "{n:x}\r\n")?;
+		write.write_all(&buf[..n])?;
+		write.write_all(b"\r\n")?;
+	}
+
+	write.write_all(b"0\r\n\r\n")
 }
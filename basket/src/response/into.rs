@@ -1,4 +1,4 @@
-use crate::Response;
+use crate::{IntoStatusCode, Response, StatusCode};
 
 #[allow(clippy::module_name_repetitions)]
 pub trait IntoResponse<'h> {
@@ -41,8 +41,15 @@ impl IntoResponse<'_> for u16 {
 	}
 }
 
-impl<'h, T> IntoResponse<'h> for (u16, T)
+impl IntoResponse<'_> for StatusCode {
+	fn into_response(self) -> Response<'static> {
+		Response::builder().status(self).build()
+	}
+}
+
+impl<'h, S, T> IntoResponse<'h> for (S, T)
 where
+	S: IntoStatusCode,
 	T: IntoResponse<'h>,
 {
 	fn into_response(self) -> Response<'static> {
@@ -1,24 +1,34 @@
 pub mod into;
 pub use into::*;
 
+use std::borrow::Cow;
 use std::io;
 use std::io::{Read, Write};
 
 #[cfg(any(feature = "json", feature = "xml"))]
 use serde::{de::DeserializeOwned, Serialize};
+use url::Url;
 
-use crate::header;
-use crate::Error;
-use crate::{extract, IntoHeader};
-
-use super::header::Header;
+#[cfg(feature = "cookies")]
+use crate::cookie::Cookie;
+use crate::header::{self, HeaderMap};
+use crate::parse::{self, Head, Parser};
+use crate::IntoHeader;
+use crate::{Error, IntoStatusCode, ParseConfig, StatusCode, Version};
 
 #[must_use]
 #[derive(Debug)]
 pub struct Response<'h> {
-	headers: Vec<Header<'h>>,
-	status: u16,
-	body: Option<Vec<u8>>,
+	pub(crate) version: Version,
+	pub(crate) headers: HeaderMap<'h>,
+	pub(crate) status: StatusCode,
+	/// The reason phrase that was received or set, if it differs from the canonical one.
+	pub(crate) reason: Option<Cow<'h, str>>,
+	pub(crate) body: Option<Vec<u8>>,
+	/// The URL of the request, if the response was received by a client.
+	pub(crate) url: Option<Url>,
+	/// The first error of the builder, which left out an invalid header or status.
+	pub(crate) error: Option<Error>,
 }
 
 impl<'h> Response<'h> {
@@ -27,10 +37,51 @@ impl<'h> Response<'h> {
 	}
 
 	#[must_use]
-	pub fn status(&self) -> u16 {
+	pub fn status(&self) -> StatusCode {
 		self.status
 	}
 
+	/// Returns the URL of the request, if the response was received by a client.
+	#[must_use]
+	pub fn url(&self) -> Option<&Url> {
+		self.url.as_ref()
+	}
+
+	/// Returns the response unchanged if its status is 2xx, and an
+	/// [`Error::Status`] holding the whole response otherwise.
+	///
+	/// # Errors
+	/// - If the status is not 2xx.
+	pub fn error_for_status(self) -> Result<Self, Error> {
+		if self.status.is_success() {
+			return Ok(self);
+		}
+
+		Err(Error::Status(Box::new(self.into_owned())))
+	}
+
+	pub(crate) fn into_owned(self) -> Response<'static> {
+		Response {
+			version: self.version,
+			headers: self.headers.into_owned(),
+			status: self.status,
+			reason: self.reason.map(|reason| Cow::Owned(reason.into_owned())),
+			body: self.body,
+			url: self.url,
+			error: self.error,
+		}
+	}
+
+	/// Returns the reason phrase that was received or set, or the canonical
+	/// one for the status code if there was none.
+	#[must_use]
+	pub fn reason(&self) -> &str {
+		self.reason
+			.as_deref()
+			.or_else(|| self.status.canonical_reason())
+			.unwrap_or_default()
+	}
+
 	/// Parses the body as JSON.
 	///
 	/// # Errors
@@ -82,46 +133,61 @@ impl<'h> Response<'h> {
 		self.body.ok_or(Error::ExpectedBody)
 	}
 
-	/// Parses a response from a reader.
+	/// Parses a response from a reader, accepting folded headers.
+	///
+	/// The reader is buffered while parsing, so it may be read past the end
+	/// of the response.
 	///
 	/// # Errors
-	/// - If the response does not adhere to the HTTP/1.1 format.
+	/// - If the response does not adhere to the HTTP/1.0 or HTTP/1.1 format.
 	pub fn from_reader<R>(reader: &mut R) -> Result<Self, Error>
 	where
 		R: Read,
 	{
-		let mut response = Self {
-			headers: vec![],
-			status: 0,
-			body: None,
-		};
-
-		extract::http_version(reader)?;
-		extract::skip(reader, b" ")?;
-
-		let status = extract::until(reader, b" ")?;
-		let status = std::str::from_utf8(&status)?.parse::<u16>()?;
+		Self::from_reader_with(reader, &ParseConfig::default().obs_fold(true))
+	}
 
-		extract::until(reader, b"\r\n")?;
+	/// Parses a response from a reader with the given options.
+	///
+	/// # Errors
+	/// - If the response does not adhere to the HTTP/1.0 or HTTP/1.1 format.
+	/// - If the response exceeds any of the limits in the [`ParseConfig`].
+	pub fn from_reader_with<R>(reader: &mut R, config: &ParseConfig) -> Result<Self, Error>
+	where
+		R: Read,
+	{
+		let (head, body) = parse::read_message(
+			&mut io::BufReader::new(reader),
+			Parser::response(config.clone()),
+		)?;
 
-		response.status = status;
+		Self::from_head(head, body)
+	}
 
-		let (headers, content_length) = header::from_reader(reader)?;
-		let mut response = Self {
-			headers,
+	/// Creates a response from a head parsed with [`Parser::parse_head`].
+	///
+	/// # Errors
+	/// - If the head is not a response head.
+	pub fn from_head(head: Head<'h>, body: Option<Vec<u8>>) -> Result<Self, Error> {
+		let Head::Response {
+			version,
 			status,
-			body: None,
+			reason,
+			headers,
+		} = head
+		else {
+			return Err(Error::InvalidFormat);
 		};
 
-		if let Some(content_length) = content_length {
-			let mut body = Vec::with_capacity(content_length);
-
-			reader.take(content_length as u64).read_to_end(&mut body)?;
-
-			response.body = Some(body);
-		}
-
-		Ok(response)
+		Ok(Self {
+			version,
+			headers,
+			status,
+			reason: Some(reason).filter(|reason| !reason.is_empty()),
+			body,
+			url: None,
+			error: None,
+		})
 	}
 
 	/// Writes the response to a writer.
@@ -132,15 +198,23 @@ impl<'h> Response<'h> {
 	where
 		W: Write,
 	{
-		write!(sink, "HTTP/1.1 {status}\r\n", status = self.status)?;
+		self.headers
+			.validate()
+			.map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+
+		// the reason phrase may be empty, but the space before it is still required
+		write!(
+			sink,
+			"{version} {status} {reason}\r\n",
+			version = self.version,
+			status = self.status.as_u16(),
+			reason = self.reason(),
+		)?;
 
 		for header in &self.headers {
-			write!(
-				sink,
-				"{name}: {value}\r\n",
-				name = header.name,
-				value = header.value
-			)?;
+			write!(sink, "{name}: ", name = header.name)?;
+			sink.write_all(header.value.as_bytes())?;
+			sink.write_all(b"\r\n")?;
 		}
 
 		write!(sink, "\r\n")?;
@@ -152,13 +226,32 @@ impl<'h> Response<'h> {
 		Ok(())
 	}
 
-	/// Returns the value of a header.
+	/// Returns the first value of a header.
 	#[must_use]
 	pub fn header(&self, name: &str) -> Option<&str> {
-		self.headers
-			.iter()
-			.find(|header| header.name.eq_ignore_ascii_case(name))
-			.map(|header| header.value.as_ref())
+		self.headers.get(name)
+	}
+
+	/// Parses a header as a [`TypedHeader`](crate::TypedHeader).
+	#[must_use]
+	pub fn typed_get<H: crate::TypedHeader>(&self) -> Option<H> {
+		self.headers.typed_get()
+	}
+
+	#[must_use]
+	pub fn headers(&self) -> &HeaderMap<'h> {
+		&self.headers
+	}
+
+	#[must_use]
+	pub fn version(&self) -> Version {
+		self.version
+	}
+
+	/// Returns `true` if the server keeps the connection open after the response.
+	#[must_use]
+	pub fn keep_alive(&self) -> bool {
+		self.version.keep_alive(&self.headers)
 	}
 }
 
@@ -167,23 +260,38 @@ impl<'h> Response<'h> {
 #[derive(Debug)]
 pub struct ResponseBuilder<'h> {
 	response: Response<'h>,
+	error: Option<Error>,
 }
 
 impl Default for ResponseBuilder<'_> {
 	fn default() -> Self {
 		Self {
 			response: Response {
-				headers: vec![],
-				status: 200,
+				version: Version::default(),
+				headers: HeaderMap::new(),
+				status: StatusCode::OK,
+				reason: None,
 				body: None,
+				url: None,
+				error: None,
 			},
+			error: None,
 		}
 	}
 }
 
 impl<'h> From<Response<'h>> for ResponseBuilder<'h> {
-	fn from(response: Response<'h>) -> Self {
-		Self { response }
+	/// Continues building a response, keeping the error of its builder, or
+	/// of a header that was added to it directly.
+	fn from(mut response: Response<'h>) -> Self {
+		let error = response.error.take().or_else(|| {
+			response
+				.headers
+				.iter()
+				.find_map(|header| header.validate().err())
+		});
+
+		Self { response, error }
 	}
 }
 
@@ -192,8 +300,49 @@ impl<'h> ResponseBuilder<'h> {
 		Self::default()
 	}
 
-	pub fn status(mut self, status: u16) -> Self {
-		self.response.status = status;
+	/// Sets the status code, which may be a [`StatusCode`] or a `u16`.
+	///
+	/// An invalid code is not set, and its error is returned by
+	/// [`ResponseBuilder::try_build`].
+	pub fn status<S>(mut self, status: S) -> Self
+	where
+		S: IntoStatusCode,
+	{
+		match status.into_status_code() {
+			Ok(status) => {
+				self.response.status = status;
+				self.response.reason = None;
+			}
+			Err(error) => {
+				self.error.get_or_insert(error);
+			}
+		}
+
+		self
+	}
+
+	/// Sets a custom reason phrase, instead of the canonical one for the status code.
+	///
+	/// An invalid reason phrase is not set, and its error is returned by
+	/// [`ResponseBuilder::try_build`].
+	pub fn reason<R>(mut self, reason: R) -> Self
+	where
+		R: Into<Cow<'h, str>>,
+	{
+		let reason = reason.into();
+
+		if header::is_valid_value(reason.as_bytes()) {
+			self.response.reason = Some(reason);
+		} else {
+			self.error.get_or_insert(Error::InvalidFormat);
+		}
+
+		self
+	}
+
+	/// Sets the HTTP version of the response. HTTP/1.1 is used by default.
+	pub fn version(mut self, version: Version) -> Self {
+		self.response.version = version;
 		self
 	}
 
@@ -204,18 +353,75 @@ impl<'h> ResponseBuilder<'h> {
 		self.header((header::CONTENT_LENGTH, len))
 	}
 
-	pub fn build(self) -> Response<'h> {
+	/// Builds the response, leaving out any invalid headers.
+	///
+	/// The [`Router`](crate::server::Router) answers with 500 instead of
+	/// sending a response that left out a header, so a handler that needs to
+	/// recover should use [`ResponseBuilder::try_build`].
+	pub fn build(mut self) -> Response<'h> {
+		self.response.error = self.error;
 		self.response
 	}
 
+	/// Builds the response.
+	///
+	/// # Errors
+	/// - If an invalid header was added.
+	pub fn try_build(self) -> Result<Response<'h>, Error> {
+		match self.error {
+			Some(error) => Err(error),
+			None => Ok(self.response),
+		}
+	}
+
+	/// Sets a header, replacing any existing values with the same name.
+	///
+	/// An invalid header is not added, and its error is returned by
+	/// [`ResponseBuilder::try_build`].
 	pub fn header<H>(mut self, header: H) -> Self
 	where
 		H: IntoHeader<'h>,
 	{
-		self.response.headers.push(header.into_header());
+		let header = header.into_header();
+
+		match header.validate() {
+			Ok(()) => {
+				self.response.headers.insert(header);
+			}
+			Err(e) => {
+				self.error.get_or_insert(e);
+			}
+		}
+
+		self
+	}
+
+	/// Adds a header, keeping any existing values with the same name.
+	///
+	/// An invalid header is not added, and its error is returned by
+	/// [`ResponseBuilder::try_build`].
+	pub fn append_header<H>(mut self, header: H) -> Self
+	where
+		H: IntoHeader<'h>,
+	{
+		let header = header.into_header();
+
+		match header.validate() {
+			Ok(()) => self.response.headers.append(header),
+			Err(e) => {
+				self.error.get_or_insert(e);
+			}
+		}
+
 		self
 	}
 
+	/// Adds a `set-cookie` header for the cookie.
+	#[cfg(feature = "cookies")]
+	pub fn cookie(self, cookie: Cookie) -> Self {
+		self.append_header(cookie)
+	}
+
 	/// Sets the body as JSON.
 	///
 	/// # Errors
@@ -0,0 +1,123 @@
+use std::{collections::hash_map::RandomState, hash::BuildHasher, io, time::Duration};
+
+use crate::{Error, Method, Response, RetryAfter, StatusCode};
+
+/// When and how often a [`Client`](crate::Client) resends a request that
+/// failed with a transient error.
+///
+/// A request is retried if the connection failed or was reset, or if the
+/// response was 429, 502, 503 or 504. Between attempts, the client waits
+/// for the `retry-after` delay if there is one, and otherwise for an
+/// exponentially growing delay with jitter.
+///
+/// Only idempotent methods are retried unless enabled with
+/// [`RetryPolicy::non_idempotent`]. Bodies set with
+/// [`RequestBuilder::body_reader`](crate::RequestBuilder::body_reader)
+/// can only be read once, so those requests are never retried, along with
+/// multipart forms that have a part read from a reader.
+#[must_use]
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+	max_attempts: u32,
+	base_delay: Duration,
+	max_delay: Duration,
+	non_idempotent: bool,
+}
+
+impl Default for RetryPolicy {
+	fn default() -> Self {
+		Self {
+			max_attempts: 3,
+			base_delay: Duration::from_millis(100),
+			max_delay: Duration::from_secs(10),
+			non_idempotent: false,
+		}
+	}
+}
+
+impl RetryPolicy {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Sets the maximum number of times the request is sent, including the
+	/// first attempt. Defaults to 3.
+	pub fn max_attempts(mut self, attempts: u32) -> Self {
+		self.max_attempts = attempts;
+		self
+	}
+
+	/// Sets the delay before the first retry, which doubles with every
+	/// attempt up to the maximum. Defaults to 100ms and 10s.
+	///
+	/// A `retry-after` delay longer than the maximum is not waited for,
+	/// and the response is returned instead.
+	pub fn backoff(mut self, base: Duration, max: Duration) -> Self {
+		self.base_delay = base;
+		self.max_delay = max;
+		self
+	}
+
+	/// Retries methods that are not idempotent, such as `POST`, which may
+	/// apply the request more than once.
+	pub fn non_idempotent(mut self, enabled: bool) -> Self {
+		self.non_idempotent = enabled;
+		self
+	}
+
+	/// Returns `true` if requests with the method may be retried.
+	pub(crate) fn allows(&self, method: &Method) -> bool {
+		self.non_idempotent || method.is_idempotent()
+	}
+
+	/// Returns how long to wait before sending the request again, or `None`
+	/// if the result should be returned.
+	pub(crate) fn delay(&self, attempt: u32, result: &Result<Response, Error>) -> Option<Duration> {
+		if attempt >= self.max_attempts {
+			return None;
+		}
+
+		match result {
+			Err(Error::Io(error)) if is_transient(error) => Some(self.backoff_delay(attempt)),
+			Ok(response) if is_transient_status(response.status()) => {
+				match response.typed_get::<RetryAfter>() {
+					Some(retry_after) => {
+						Some(retry_after.delay()).filter(|delay| *delay <= self.max_delay)
+					}
+					None => Some(self.backoff_delay(attempt)),
+				}
+			}
+			_ => None,
+		}
+	}
+
+	/// Returns a random delay between half and all of the exponential delay,
+	/// so that clients failing at the same time do not retry at the same time.
+	fn backoff_delay(&self, attempt: u32) -> Duration {
+		let delay = self
+			.base_delay
+			.saturating_mul(2u32.saturating_pow(attempt - 1))
+			.min(self.max_delay);
+		let half = delay / 2;
+		let range = u64::try_from(half.as_nanos()).unwrap_or(u64::MAX);
+		let random = RandomState::new().hash_one(attempt);
+
+		half + Duration::from_nanos(random % range.saturating_add(1))
+	}
+}
+
+fn is_transient(error: &io::Error) -> bool {
+	matches!(
+		error.kind(),
+		io::ErrorKind::BrokenPipe
+			| io::ErrorKind::ConnectionAborted
+			| io::ErrorKind::ConnectionRefused
+			| io::ErrorKind::ConnectionReset
+			| io::ErrorKind::TimedOut
+			| io::ErrorKind::UnexpectedEof
+	)
+}
+
+fn is_transient_status(status: StatusCode) -> bool {
+	matches!(status.as_u16(), 429 | 502 | 503 | 504)
+}
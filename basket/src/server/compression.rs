@@ -0,0 +1,246 @@
+use std::io::Write;
+
+use crate::{header, Request, Response};
+
+/// Content types that are already compressed, and would not benefit from
+/// being compressed again.
+const COMPRESSED_TYPES: &[&str] = &[
+	"application/gzip",
+	"application/x-gzip",
+	"application/zip",
+	"application/zstd",
+	"application/x-7z-compressed",
+	"application/x-bzip2",
+	"application/x-rar-compressed",
+	"font/woff",
+	"font/woff2",
+];
+
+/// A content coding supported by [`Compression`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+	Brotli,
+	Gzip,
+}
+
+impl Encoding {
+	#[must_use]
+	pub fn as_str(&self) -> &str {
+		match self {
+			Self::Brotli => "br",
+			Self::Gzip => "gzip",
+		}
+	}
+}
+
+/// Compresses response bodies with the best encoding accepted by the client.
+///
+/// Bodies smaller than the threshold, bodies that already have a
+/// `content-encoding` and bodies with an already-compressed content type
+/// are sent as-is.
+#[derive(Debug, Clone, Copy)]
+pub struct Compression {
+	threshold: usize,
+	brotli: bool,
+	gzip: bool,
+}
+
+impl Default for Compression {
+	fn default() -> Self {
+		Self {
+			threshold: 1024,
+			brotli: true,
+			gzip: true,
+		}
+	}
+}
+
+impl Compression {
+	#[must_use]
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Sets the minimum body size, in bytes, that will be compressed.
+	#[must_use]
+	pub fn threshold(mut self, threshold: usize) -> Self {
+		self.threshold = threshold;
+		self
+	}
+
+	/// Enables or disables brotli compression.
+	#[must_use]
+	pub fn brotli(mut self, enabled: bool) -> Self {
+		self.brotli = enabled;
+		self
+	}
+
+	/// Enables or disables gzip compression.
+	#[must_use]
+	pub fn gzip(mut self, enabled: bool) -> Self {
+		self.gzip = enabled;
+		self
+	}
+
+	/// Picks the encoding to use for a request based on its `accept-encoding` header.
+	///
+	/// Encodings are ranked by their q-value, with brotli preferred over gzip
+	/// when both are equally acceptable.
+	#[must_use]
+	pub fn negotiate(&self, request: &Request) -> Option<Encoding> {
+		let accept = request.header(header::ACCEPT_ENCODING)?;
+
+		let mut brotli = None;
+		let mut gzip = None;
+		let mut wildcard = None;
+
+		for item in accept.split(',') {
+			let mut params = item.split(';');
+			let coding = params.next().unwrap_or_default().trim();
+			let quality = params
+				.filter_map(|param| {
+					let (name, value) = param.split_once('=')?;
+
+					// parameter names are case-insensitive, so `Q=0` also refuses the coding
+					name.trim().eq_ignore_ascii_case("q").then_some(value)
+				})
+				.find_map(|q| q.trim().parse::<f32>().ok())
+				.unwrap_or(1.0);
+
+			if coding.eq_ignore_ascii_case("br") {
+				brotli = Some(quality);
+			} else if coding.eq_ignore_ascii_case("gzip") || coding.eq_ignore_ascii_case("x-gzip") {
+				gzip = Some(quality);
+			} else if coding == "*" {
+				wildcard = Some(quality);
+			}
+		}
+
+		let candidates = [
+			(Encoding::Brotli, self.brotli, brotli),
+			(Encoding::Gzip, self.gzip, gzip),
+		];
+
+		let mut best: Option<(Encoding, f32)> = None;
+
+		for (encoding, enabled, quality) in candidates {
+			let quality = quality.or(wildcard).unwrap_or(0.0);
+
+			if !enabled || quality <= 0.0 {
+				continue;
+			}
+
+			if best.is_none_or(|(_, q)| quality > q) {
+				best = Some((encoding, quality));
+			}
+		}
+
+		best.map(|(encoding, _)| encoding)
+	}
+
+	/// Compresses the body of the response with the provided encoding, if it
+	/// is eligible for compression.
+	pub fn compress<'h>(
+		&self,
+		encoding: Option<Encoding>,
+		mut response: Response<'h>,
+	) -> Response<'h> {
+		if !self.is_compressible(&response) {
+			return response;
+		}
+
+		// the body is eligible, so the representation depends on the request
+		let vary = match response.header(header::VARY) {
+			Some(vary)
+				if vary
+					.split(',')
+					.any(|v| v.trim().eq_ignore_ascii_case(header::ACCEPT_ENCODING)) =>
+			{
+				None
+			}
+			Some(vary) => Some(format!("{vary}, {}", header::ACCEPT_ENCODING)),
+			None => Some(header::ACCEPT_ENCODING.to_string()),
+		};
+
+		if let Some(vary) = vary {
+			response.headers.insert((header::VARY, vary));
+		}
+
+		let Some(encoding) = encoding else {
+			return response;
+		};
+
+		let Some(body) = response.body.as_deref() else {
+			return response;
+		};
+
+		let Ok(compressed) = encode(encoding, body) else {
+			return response;
+		};
+
+		// no point in sending a bigger body
+		if compressed.len() >= body.len() {
+			return response;
+		}
+
+		response
+			.headers
+			.insert((header::CONTENT_LENGTH, compressed.len()));
+		response
+			.headers
+			.insert((header::CONTENT_ENCODING, encoding.as_str().to_string()));
+		response.body = Some(compressed);
+
+		response
+	}
+
+	fn is_compressible(&self, response: &Response) -> bool {
+		let Some(body) = response.body.as_ref() else {
+			return false;
+		};
+
+		if body.len() < self.threshold || response.header(header::CONTENT_ENCODING).is_some() {
+			return false;
+		}
+
+		let Some(content_type) = response.header(header::CONTENT_TYPE) else {
+			return true;
+		};
+
+		let mime = content_type
+			.split(';')
+			.next()
+			.unwrap_or_default()
+			.trim()
+			.to_ascii_lowercase();
+
+		if mime == "image/svg+xml" {
+			return true;
+		}
+
+		!(mime.starts_with("image/")
+			|| mime.starts_with("audio/")
+			|| mime.starts_with("video/")
+			|| COMPRESSED_TYPES.contains(&mime.as_str()))
+	}
+}
+
+fn encode(encoding: Encoding, body: &[u8]) -> std::io::Result<Vec<u8>> {
+	match encoding {
+		Encoding::Brotli => {
+			let mut encoder = brotli::CompressorWriter::new(Vec::new(), 4096, 5, 22);
+
+			encoder.write_all(body)?;
+
+			// finishes the stream
+			Ok(encoder.into_inner())
+		}
+		Encoding::Gzip => {
+			let mut encoder =
+				flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+
+			encoder.write_all(body)?;
+			encoder.finish()
+		}
+	}
+}
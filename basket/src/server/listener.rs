@@ -0,0 +1,70 @@
+#[cfg(unix)]
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::{
+	io::{self, Read, Write},
+	net::{TcpListener, TcpStream},
+};
+
+/// A source of connections that a [`Router`](super::Router) can listen on,
+/// such as a [`TcpListener`] or a [`UnixListener`].
+pub trait Listener {
+	type Stream: Read + Write + Send + 'static;
+
+	/// Waits for the next connection.
+	///
+	/// # Errors
+	/// - If the connection could not be accepted.
+	fn accept(&self) -> io::Result<Self::Stream>;
+}
+
+impl Listener for TcpListener {
+	type Stream = TcpStream;
+
+	fn accept(&self) -> io::Result<Self::Stream> {
+		TcpListener::accept(self).map(|(stream, _)| stream)
+	}
+}
+
+#[cfg(unix)]
+impl Listener for UnixListener {
+	type Stream = UnixStream;
+
+	fn accept(&self) -> io::Result<Self::Stream> {
+		UnixListener::accept(self).map(|(stream, _)| stream)
+	}
+}
+
+/// A source of connections that a [`Router`](super::Router) can serve
+/// without blocking, such as a tokio `TcpListener` or `UnixListener`.
+#[cfg(feature = "async")]
+pub trait AsyncListener {
+	type Stream: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin;
+
+	/// Waits for the next connection.
+	///
+	/// # Errors
+	/// - If the connection could not be accepted.
+	fn accept(&self) -> impl std::future::Future<Output = io::Result<Self::Stream>>;
+}
+
+#[cfg(feature = "async")]
+impl AsyncListener for tokio::net::TcpListener {
+	type Stream = tokio::net::TcpStream;
+
+	async fn accept(&self) -> io::Result<Self::Stream> {
+		tokio::net::TcpListener::accept(self)
+			.await
+			.map(|(stream, _)| stream)
+	}
+}
+
+#[cfg(all(unix, feature = "async"))]
+impl AsyncListener for tokio::net::UnixListener {
+	type Stream = tokio::net::UnixStream;
+
+	async fn accept(&self) -> io::Result<Self::Stream> {
+		tokio::net::UnixListener::accept(self)
+			.await
+			.map(|(stream, _)| stream)
+	}
+}
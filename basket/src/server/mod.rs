@@ -0,0 +1,432 @@
+#[cfg(feature = "compression")]
+mod compression;
+mod listener;
+#[cfg(feature = "sessions")]
+mod session;
+
+#[cfg(feature = "compression")]
+pub use compression::*;
+pub use listener::*;
+#[cfg(feature = "sessions")]
+pub use session::*;
+
+use std::io::{self, Read, Write};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+use crate::parse::{self, BodyReader, Head, Parser};
+use crate::{header, Error, Method, ParseConfig, Request, Response, ResponseBuilder, StatusCode};
+
+pub type Handler<S> = fn(S, Request) -> Response;
+
+/// A simple HTTP router.
+#[must_use]
+#[derive(Debug)]
+pub struct Router<'a, S> {
+	routes: Vec<(Option<Method>, &'a str, Handler<S>)>,
+	state: S,
+	parse_config: ParseConfig,
+	#[cfg(feature = "compression")]
+	compression: Option<Compression>,
+	#[cfg(feature = "sessions")]
+	session: Option<SessionLayer>,
+}
+
+impl<'a, S> Router<'a, S>
+where
+	S: Clone,
+{
+	pub fn new(state: S) -> Self
+	where
+		S: Clone,
+	{
+		Self {
+			routes: vec![],
+			state,
+			parse_config: ParseConfig::default().max_body_size(16 * 1024 * 1024),
+			#[cfg(feature = "compression")]
+			compression: None,
+			#[cfg(feature = "sessions")]
+			session: None,
+		}
+	}
+
+	/// Compresses response bodies according to the request's `accept-encoding` header.
+	#[cfg(feature = "compression")]
+	pub fn compression(mut self, compression: Compression) -> Self {
+		self.compression = Some(compression);
+		self
+	}
+
+	/// Adds a [`Session`] to every request, which handlers can get with [`Session::from_request`].
+	#[cfg(feature = "sessions")]
+	pub fn session(mut self, session: SessionLayer) -> Self {
+		self.session = Some(session);
+		self
+	}
+
+	/// Sets the options used to parse requests. By default, request bodies
+	/// are limited to 16 MiB.
+	pub fn parse_config(mut self, config: ParseConfig) -> Self {
+		self.parse_config = config;
+		self
+	}
+
+	/// Adds a new route to the router. To require a trailing slash, add a slash to the end of the route.
+	/// Routes match any method, including extension methods such as `PROPFIND`.
+	pub fn route(mut self, route: &'a str, handler: Handler<S>) -> Self {
+		self.routes.push((None, route, handler));
+		self
+	}
+
+	/// Adds a new route that only matches requests with the given method.
+	///
+	/// If a path only has routes for other methods, the request is answered
+	/// with 405 and an `allow` header listing them.
+	pub fn route_method(mut self, method: Method, route: &'a str, handler: Handler<S>) -> Self {
+		self.routes.push((Some(method), route, handler));
+		self
+	}
+
+	/// Listens for incoming connections on the provided listener, which may
+	/// be a [`TcpListener`](std::net::TcpListener) or a
+	/// [`UnixListener`](std::os::unix::net::UnixListener).
+	///
+	/// Requests that cannot be parsed are answered with an error status, such
+	/// as 414, 431 or 413 when they exceed the limits of the [`ParseConfig`].
+	///
+	/// The body of a `multipart/*` request is not read into memory, but
+	/// streamed from the connection as the handler reads it with
+	/// [`Request::multipart`].
+	///
+	/// # Errors
+	/// - If an error occurs while accepting a connection.
+	pub fn listen<L>(self, listener: &L) -> Result<!, Error>
+	where
+		L: Listener,
+	{
+		loop {
+			let stream = Arc::new(Mutex::new(listener.accept()?));
+			let mut buf = Vec::new();
+
+			let response = self.handle(read_request(&stream, &mut buf, &self.parse_config));
+			let mut stream = lock(&stream);
+
+			let _ = response.write(&mut *stream);
+			let _ = stream.flush();
+		}
+	}
+
+	/// Listens for incoming connections on the provided listener without
+	/// blocking, handling requests exactly like [`Router::listen`].
+	///
+	/// Each connection is handled in a task of its own, so the router is
+	/// shared between them. Handlers are still called directly, so they
+	/// should not block for long, and every request body is read into memory
+	/// before its handler is called.
+	///
+	/// # Errors
+	/// - If an error occurs while accepting a connection.
+	#[cfg(feature = "async")]
+	pub async fn serve<L>(self, listener: &L) -> Result<!, Error>
+	where
+		L: AsyncListener,
+		L::Stream: Send + 'static,
+		S: Send + Sync + 'static,
+		'a: 'static,
+	{
+		use tokio::io::AsyncWriteExt;
+
+		let router = Arc::new(self);
+
+		loop {
+			let mut stream = listener.accept().await?;
+			let router = Arc::clone(&router);
+
+			tokio::spawn(async move {
+				let mut buf = Vec::new();
+				let mut message = Vec::new();
+
+				let response = router
+					.handle(read_request_async(&mut stream, &mut buf, &router.parse_config).await);
+
+				if response.write(&mut message).is_ok() {
+					let _ = stream.write_all(&message).await;
+					let _ = stream.flush().await;
+				}
+			});
+		}
+	}
+
+	/// Returns the response to a request, or to the error that stopped it
+	/// from being read.
+	///
+	/// Errors writing the response are ignored by the caller, since the
+	/// client may have already gone away, which is not an error for the server.
+	fn handle<'r>(&self, request: Result<Request<'r>, Error>) -> Response<'r> {
+		match request {
+			Ok(request) => self.respond(request),
+			Err(error) => error_response(&error),
+		}
+	}
+
+	/// Routes a parsed request to its handler, and applies sessions and compression.
+	fn respond<'r>(
+		&self,
+		#[cfg_attr(not(feature = "sessions"), allow(unused_mut))] mut request: Request<'r>,
+	) -> Response<'r> {
+		#[cfg(feature = "sessions")]
+		let session = self
+			.session
+			.as_ref()
+			.map(|layer| (layer, layer.start(&mut request)));
+
+		#[cfg(feature = "compression")]
+		let encoding = self
+			.compression
+			.as_ref()
+			.and_then(|compression| compression.negotiate(&request));
+
+		let path = request.url.path();
+
+		// remove trailing slash, unless it's the root path
+		let path = if path.len() > 1 && path.ends_with('/') {
+			&path[..path.len() - 1]
+		} else {
+			path
+		};
+
+		let matching = self
+			.routes
+			.iter()
+			.filter(|(_, route, _)| path.starts_with(route));
+		let head = request.method == Method::Head;
+		let version = request.version;
+		let keep_alive = request.keep_alive();
+		let handler = matching
+			.clone()
+			.find(|(method, _, _)| method.as_ref().is_none_or(|m| *m == request.method))
+			// HEAD is answered by the GET handler, unless it has a route of its own
+			.or_else(|| {
+				matching
+					.clone()
+					.find(|(method, _, _)| head && *method == Some(Method::Get))
+			});
+
+		let response = if let Some((_, _, handler)) = handler {
+			handler(self.state.clone(), request)
+		} else {
+			let mut allowed = matching
+				.filter_map(|(method, _, _)| method.as_ref().map(Method::as_str))
+				.collect::<Vec<_>>();
+
+			if allowed.contains(&"GET") && !allowed.contains(&"HEAD") {
+				allowed.push("HEAD");
+			}
+
+			if allowed.is_empty() {
+				Response::builder().status(StatusCode::NOT_FOUND).build()
+			} else {
+				Response::builder()
+					.status(StatusCode::METHOD_NOT_ALLOWED)
+					.header(("allow", allowed.join(", ")))
+					.build()
+			}
+		};
+
+		#[cfg(feature = "sessions")]
+		let response = match session {
+			Some((layer, session)) => layer.finish(&session, response).unwrap_or_else(|_| {
+				Response::builder()
+					.status(StatusCode::INTERNAL_SERVER_ERROR)
+					.build()
+			}),
+			None => response,
+		};
+
+		#[cfg(feature = "compression")]
+		let response = match self.compression.as_ref() {
+			Some(compression) => compression.compress(encoding, response),
+			None => response,
+		};
+
+		let mut response: ResponseBuilder = response.into();
+
+		// every connection is closed after one response, which HTTP/1.0 clients expect by default
+		if keep_alive {
+			response = response.header((header::CONNECTION, "close"));
+		}
+
+		// a response that left out an invalid header is not sent, since it could be missing anything
+		let mut response = response
+			.version(version)
+			.header(("server", "basket"))
+			.try_build()
+			.unwrap_or_else(|_| {
+				Response::builder()
+					.status(StatusCode::INTERNAL_SERVER_ERROR)
+					.version(version)
+					.header((header::CONNECTION, "close"))
+					.header(("server", "basket"))
+					.build()
+			});
+
+		// the headers, including content-length, describe the body that GET would have sent
+		if head {
+			response.body = None;
+		}
+
+		response
+	}
+}
+
+/// A connection that the router responds on, shared with the body of a
+/// request that its handler streams from the connection.
+struct Shared<S>(Arc<Mutex<S>>);
+
+impl<S: Read> Read for Shared<S> {
+	fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+		lock(&self.0).read(buf)
+	}
+}
+
+fn lock<S>(stream: &Mutex<S>) -> MutexGuard<'_, S> {
+	stream.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Returns `true` if the body of the request is left on the connection,
+/// for the handler to stream as it parses the parts.
+#[cfg(feature = "multipart")]
+fn streams_body(head: &Head) -> bool {
+	head.headers()
+		.get(header::CONTENT_TYPE)
+		.and_then(crate::multipart::boundary)
+		.is_some()
+}
+
+#[cfg(not(feature = "multipart"))]
+fn streams_body(_: &Head) -> bool {
+	false
+}
+
+/// Reads a request whose headers borrow from the buffer its head was read into,
+/// so no allocation is needed per header.
+///
+/// The body is read into memory, unless [`streams_body`] leaves it on the connection.
+fn read_request<'b, S>(
+	stream: &Arc<Mutex<S>>,
+	buf: &'b mut Vec<u8>,
+	config: &ParseConfig,
+) -> Result<Request<'b>, Error>
+where
+	S: Read + Send + 'static,
+{
+	let mut parser = Parser::request(config.clone());
+	let mut stream = Shared(Arc::clone(stream));
+	let mut chunk = [0; 4096];
+
+	let len = loop {
+		if let Some(len) = parser.head_len(buf)? {
+			break len;
+		}
+
+		let read = stream.read(&mut chunk)?;
+
+		extend_head(buf, &chunk[..read])?;
+	};
+
+	let (head, rest) = split_head(&mut parser, buf, len)?;
+
+	if streams_body(&head) {
+		// the handler may keep the body past this buffer, so the start of it is copied
+		let reader = io::BufReader::new(io::Cursor::new(rest.to_vec()).chain(stream));
+		let mut request = Request::from_head(head, None)?;
+
+		request.set_body_reader(BodyReader::new(reader, parser));
+
+		return Ok(request);
+	}
+
+	let mut reader = io::BufReader::new(rest.chain(stream));
+	let body = parse::read_body(&mut reader, &mut parser)?;
+
+	Request::from_head(head, body)
+}
+
+/// Reads a request without blocking, like [`read_request`] but always
+/// reading the body into memory.
+#[cfg(feature = "async")]
+async fn read_request_async<'b, S>(
+	stream: &mut S,
+	buf: &'b mut Vec<u8>,
+	config: &ParseConfig,
+) -> Result<Request<'b>, Error>
+where
+	S: tokio::io::AsyncRead + Unpin,
+{
+	use tokio::io::AsyncReadExt;
+
+	let mut parser = Parser::request(config.clone());
+	let mut chunk = [0; 4096];
+
+	let len = loop {
+		if let Some(len) = parser.head_len(buf)? {
+			break len;
+		}
+
+		let read = stream.read(&mut chunk).await?;
+
+		extend_head(buf, &chunk[..read])?;
+	};
+
+	let (head, rest) = split_head(&mut parser, buf, len)?;
+	let mut reader = tokio::io::BufReader::new(AsyncReadExt::chain(rest, stream));
+	let body = parse::read_body_async(&mut reader, &mut parser).await?;
+
+	Request::from_head(head, body)
+}
+
+/// Appends a chunk read from the connection to the buffered head, failing
+/// if the connection was closed before the head was complete.
+fn extend_head(buf: &mut Vec<u8>, chunk: &[u8]) -> Result<(), Error> {
+	if chunk.is_empty() {
+		return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+	}
+
+	buf.extend_from_slice(chunk);
+
+	Ok(())
+}
+
+/// Parses the first `len` bytes of the buffer as the head, and returns it
+/// along with anything read past it, which is the start of the body.
+fn split_head<'b>(
+	parser: &mut Parser,
+	buf: &'b [u8],
+	len: usize,
+) -> Result<(Head<'b>, &'b [u8]), Error> {
+	let (head, rest) = buf.split_at(len);
+
+	Ok((parser.parse_head(head)?, rest))
+}
+
+/// Returns the response to send when a request could not be parsed.
+fn error_response(error: &Error) -> Response<'static> {
+	Response::builder()
+		.status(error_status(error))
+		.header((header::CONNECTION, "close"))
+		.build()
+}
+
+/// Returns the status to respond with when a request could not be parsed.
+fn error_status(error: &Error) -> StatusCode {
+	match error {
+		Error::StartLineTooLong => StatusCode::URI_TOO_LONG,
+		Error::HeaderTooLarge | Error::HeadersTooLarge | Error::TooManyHeaders => {
+			StatusCode::REQUEST_HEADER_FIELDS_TOO_LARGE
+		}
+		Error::BodyTooLarge => StatusCode::CONTENT_TOO_LARGE,
+		Error::UnknownMethod => StatusCode::NOT_IMPLEMENTED,
+		Error::UnsupportedHttp => StatusCode::HTTP_VERSION_NOT_SUPPORTED,
+		_ => StatusCode::BAD_REQUEST,
+	}
+}
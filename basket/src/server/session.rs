@@ -0,0 +1,405 @@
+use std::{
+	collections::HashMap,
+	fmt::{self, Write as _},
+	sync::{Arc, Mutex, MutexGuard, PoisonError},
+	time::{Duration, SystemTime},
+};
+
+use crate::{
+	cookie::{Cookie, SameSite},
+	Error, Request, Response, ResponseBuilder,
+};
+
+const ID_LEN: usize = 32;
+/// The number of records a [`MemoryStore`] holds before it first removes expired ones.
+const SWEEP_MIN: usize = 64;
+
+/// The data stored in a session, along with when it expires.
+#[derive(Debug, Clone)]
+pub struct Record {
+	pub data: HashMap<String, String>,
+	pub expires: SystemTime,
+}
+
+impl Record {
+	#[must_use]
+	pub fn is_expired(&self) -> bool {
+		self.expires <= SystemTime::now()
+	}
+}
+
+/// A backend that session records are loaded from and saved to.
+///
+/// Stores must not return records that have expired.
+pub trait SessionStore: Send + Sync {
+	/// Loads the record with the given session id.
+	///
+	/// # Errors
+	/// - If the backend could not be reached.
+	fn load(&self, id: &str) -> Result<Option<Record>, Error>;
+
+	/// Saves the record under the given session id, replacing any existing record.
+	///
+	/// # Errors
+	/// - If the backend could not be reached.
+	fn save(&self, id: &str, record: &Record) -> Result<(), Error>;
+
+	/// Deletes the record with the given session id.
+	///
+	/// # Errors
+	/// - If the backend could not be reached.
+	fn delete(&self, id: &str) -> Result<(), Error>;
+}
+
+/// A session store that keeps records in memory, which are lost when the
+/// server stops.
+#[derive(Debug, Default)]
+pub struct MemoryStore {
+	records: Mutex<Records>,
+}
+
+#[derive(Debug, Default)]
+struct Records {
+	map: HashMap<String, Record>,
+	/// The number of records at which expired ones are next removed.
+	sweep_at: usize,
+}
+
+impl MemoryStore {
+	#[must_use]
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Returns the number of records, including expired ones that have not
+	/// been removed yet.
+	#[must_use]
+	pub fn len(&self) -> usize {
+		self.lock().map.len()
+	}
+
+	#[must_use]
+	pub fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
+
+	fn lock(&self) -> MutexGuard<'_, Records> {
+		self.records.lock().unwrap_or_else(PoisonError::into_inner)
+	}
+}
+
+impl SessionStore for MemoryStore {
+	fn load(&self, id: &str) -> Result<Option<Record>, Error> {
+		let records = &mut self.lock().map;
+
+		// expired records are removed the next time they are accessed
+		if records.get(id).is_some_and(Record::is_expired) {
+			records.remove(id);
+		}
+
+		Ok(records.get(id).cloned())
+	}
+
+	fn save(&self, id: &str, record: &Record) -> Result<(), Error> {
+		let mut records = self.lock();
+
+		records.map.insert(id.to_string(), record.clone());
+
+		// records that are never loaded again would otherwise stay forever, and sweeping
+		// whenever the store doubles in size keeps the cost per save constant on average
+		if records.map.len() >= records.sweep_at {
+			records.map.retain(|_, record| !record.is_expired());
+			records.sweep_at = (records.map.len() * 2).max(SWEEP_MIN);
+		}
+
+		Ok(())
+	}
+
+	fn delete(&self, id: &str) -> Result<(), Error> {
+		self.lock().map.remove(id);
+
+		Ok(())
+	}
+}
+
+#[derive(Debug, Default)]
+struct State {
+	/// The id sent by the client, if any.
+	id: Option<String>,
+	/// The session data, which is loaded from the store on first access.
+	data: Option<HashMap<String, String>>,
+	dirty: bool,
+	rotate: bool,
+	destroy: bool,
+}
+
+/// A handle to the session of the current request.
+///
+/// The session is only loaded from the store once it is first accessed,
+/// and is saved after the handler returns if it was modified.
+#[derive(Clone)]
+pub struct Session {
+	state: Arc<Mutex<State>>,
+	store: Arc<dyn SessionStore>,
+}
+
+impl fmt::Debug for Session {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("Session")
+			.field("state", &self.state)
+			.finish_non_exhaustive()
+	}
+}
+
+impl Session {
+	/// Returns the session added to the request by the [`SessionLayer`].
+	#[must_use]
+	pub fn from_request(request: &Request) -> Option<Self> {
+		request.extensions.get::<Self>().cloned()
+	}
+
+	/// Returns the value stored under the key.
+	///
+	/// # Errors
+	/// - If the session could not be loaded from the store.
+	pub fn get(&self, key: &str) -> Result<Option<String>, Error> {
+		Ok(self
+			.load()?
+			.data
+			.as_ref()
+			.and_then(|data| data.get(key).cloned()))
+	}
+
+	/// Stores a value under the key, returning the previous value.
+	///
+	/// # Errors
+	/// - If the session could not be loaded from the store.
+	pub fn insert<K: Into<String>, V: Into<String>>(
+		&self,
+		key: K,
+		value: V,
+	) -> Result<Option<String>, Error> {
+		let mut state = self.load()?;
+
+		state.dirty = true;
+
+		Ok(state
+			.data
+			.get_or_insert_with(HashMap::new)
+			.insert(key.into(), value.into()))
+	}
+
+	/// Removes the value stored under the key.
+	///
+	/// # Errors
+	/// - If the session could not be loaded from the store.
+	pub fn remove(&self, key: &str) -> Result<Option<String>, Error> {
+		let mut state = self.load()?;
+		let previous = state.data.as_mut().and_then(|data| data.remove(key));
+
+		state.dirty |= previous.is_some();
+
+		Ok(previous)
+	}
+
+	/// Assigns a new id to the session while keeping its data.
+	///
+	/// This should be called whenever the privilege level changes, such as
+	/// on login, to prevent session fixation.
+	///
+	/// # Errors
+	/// - If the session could not be loaded from the store.
+	pub fn rotate(&self) -> Result<(), Error> {
+		let mut state = self.load()?;
+
+		state.rotate = true;
+		state.dirty = true;
+
+		Ok(())
+	}
+
+	/// Deletes the session from the store and removes the cookie from the client.
+	pub fn destroy(&self) {
+		let mut state = self.lock();
+
+		state.destroy = true;
+		state.data = Some(HashMap::new());
+	}
+
+	fn lock(&self) -> MutexGuard<'_, State> {
+		self.state.lock().unwrap_or_else(PoisonError::into_inner)
+	}
+
+	fn load(&self) -> Result<MutexGuard<'_, State>, Error> {
+		let mut state = self.lock();
+
+		if state.data.is_none() {
+			let record = match state.id.as_deref() {
+				Some(id) => self.store.load(id)?,
+				None => None,
+			};
+
+			// the client sent an unknown or expired id, so it will be replaced when saved
+			if record.is_none() {
+				state.rotate = state.id.is_some();
+			}
+
+			state.data = Some(record.map(|record| record.data).unwrap_or_default());
+		}
+
+		Ok(state)
+	}
+}
+
+/// Adds a [`Session`] to every request handled by a [`Router`](super::Router).
+///
+/// The session id is stored in an `HttpOnly` cookie, and the session data
+/// is kept in a [`SessionStore`].
+#[derive(Clone)]
+pub struct SessionLayer {
+	store: Arc<dyn SessionStore>,
+	cookie_name: String,
+	ttl: Duration,
+	secure: bool,
+	same_site: SameSite,
+}
+
+impl fmt::Debug for SessionLayer {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("SessionLayer")
+			.field("cookie_name", &self.cookie_name)
+			.field("ttl", &self.ttl)
+			.field("secure", &self.secure)
+			.field("same_site", &self.same_site)
+			.finish_non_exhaustive()
+	}
+}
+
+impl SessionLayer {
+	pub fn new<S: SessionStore + 'static>(store: S) -> Self {
+		Self {
+			store: Arc::new(store),
+			cookie_name: "session".into(),
+			ttl: Duration::from_hours(24),
+			secure: false,
+			same_site: SameSite::Lax,
+		}
+	}
+
+	/// Sets the name of the cookie that holds the session id.
+	#[must_use]
+	pub fn cookie_name<N: Into<String>>(mut self, name: N) -> Self {
+		self.cookie_name = name.into();
+		self
+	}
+
+	/// Sets how long a session lives after it was last modified.
+	#[must_use]
+	pub fn ttl(mut self, ttl: Duration) -> Self {
+		self.ttl = ttl;
+		self
+	}
+
+	/// Only sends the session cookie over secure connections.
+	#[must_use]
+	pub fn secure(mut self, secure: bool) -> Self {
+		self.secure = secure;
+		self
+	}
+
+	#[must_use]
+	pub fn same_site(mut self, same_site: SameSite) -> Self {
+		self.same_site = same_site;
+		self
+	}
+
+	/// Attaches a session to the request, without loading it from the store.
+	pub(crate) fn start(&self, request: &mut Request) -> Session {
+		let id = request
+			.cookie(&self.cookie_name)
+			.filter(|id| id.len() == ID_LEN * 2)
+			.map(ToString::to_string);
+
+		let session = Session {
+			state: Arc::new(Mutex::new(State {
+				id,
+				..State::default()
+			})),
+			store: Arc::clone(&self.store),
+		};
+
+		request.extensions.insert(session.clone());
+		session
+	}
+
+	/// Saves the session if it was modified, and sets the session cookie on the response.
+	///
+	/// # Errors
+	/// - If the session could not be saved to the store.
+	pub(crate) fn finish<'h>(
+		&self,
+		session: &Session,
+		response: Response<'h>,
+	) -> Result<Response<'h>, Error> {
+		let state = session.lock();
+
+		if state.destroy {
+			if let Some(id) = &state.id {
+				self.store.delete(id)?;
+			}
+
+			let removal = Cookie::removal(self.cookie_name.as_str()).path("/");
+
+			return Ok(ResponseBuilder::from(response)
+				.append_header(removal)
+				.build());
+		}
+
+		if !state.dirty {
+			return Ok(response);
+		}
+
+		let id = match &state.id {
+			Some(id) if !state.rotate => id.clone(),
+			previous => {
+				if let Some(previous) = previous {
+					self.store.delete(previous)?;
+				}
+
+				generate_id()?
+			}
+		};
+
+		let record = Record {
+			data: state.data.clone().unwrap_or_default(),
+			expires: SystemTime::now() + self.ttl,
+		};
+
+		self.store.save(&id, &record)?;
+
+		let cookie = Cookie::builder(self.cookie_name.as_str(), id)
+			.path("/")
+			.max_age(self.ttl)
+			.http_only(true)
+			.secure(self.secure)
+			.same_site(self.same_site);
+
+		Ok(ResponseBuilder::from(response)
+			.append_header(cookie)
+			.build())
+	}
+}
+
+fn generate_id() -> Result<String, Error> {
+	let mut bytes = [0; ID_LEN];
+
+	getrandom::getrandom(&mut bytes).map_err(|e| Error::Io(e.into()))?;
+
+	let mut id = String::with_capacity(ID_LEN * 2);
+
+	for byte in bytes {
+		let _ = write!(id, "{byte:02x}");
+	}
+
+	Ok(id)
+}
@@ -0,0 +1,214 @@
+use std::{fmt, str::FromStr};
+
+use crate::Error;
+
+/// An HTTP status code, which is always in the range 100–999.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct StatusCode(u16);
+
+impl StatusCode {
+	/// Creates a status code, which must have three digits.
+	///
+	/// # Errors
+	/// - If the code is not in the range 100–999.
+	pub fn from_u16(code: u16) -> Result<Self, Error> {
+		if (100..1000).contains(&code) {
+			Ok(Self(code))
+		} else {
+			Err(Error::InvalidStatus(code))
+		}
+	}
+
+	#[must_use]
+	pub fn as_u16(self) -> u16 {
+		self.0
+	}
+
+	/// Returns `true` for 1xx codes.
+	#[must_use]
+	pub fn is_informational(self) -> bool {
+		(100..200).contains(&self.0)
+	}
+
+	/// Returns `true` for 2xx codes.
+	#[must_use]
+	pub fn is_success(self) -> bool {
+		(200..300).contains(&self.0)
+	}
+
+	/// Returns `true` for 3xx codes.
+	#[must_use]
+	pub fn is_redirect(self) -> bool {
+		(300..400).contains(&self.0)
+	}
+
+	/// Returns `true` for 4xx codes.
+	#[must_use]
+	pub fn is_client_error(self) -> bool {
+		(400..500).contains(&self.0)
+	}
+
+	/// Returns `true` for 5xx codes.
+	#[must_use]
+	pub fn is_server_error(self) -> bool {
+		(500..600).contains(&self.0)
+	}
+}
+
+macro_rules! status_codes {
+	($(($code:literal, $name:ident, $reason:literal),)*) => {
+		impl StatusCode {
+			$(
+				pub const $name: StatusCode = StatusCode($code);
+			)*
+
+			/// Returns the reason phrase registered for the code, such as `Not Found` for 404.
+			#[must_use]
+			pub fn canonical_reason(self) -> Option<&'static str> {
+				match self.0 {
+					$($code => Some($reason),)*
+					_ => None,
+				}
+			}
+		}
+	};
+}
+
+status_codes! {
+	(100, CONTINUE, "Continue"),
+	(101, SWITCHING_PROTOCOLS, "Switching Protocols"),
+	(102, PROCESSING, "Processing"),
+	(103, EARLY_HINTS, "Early Hints"),
+	(200, OK, "OK"),
+	(201, CREATED, "Created"),
+	(202, ACCEPTED, "Accepted"),
+	(203, NON_AUTHORITATIVE_INFORMATION, "Non-Authoritative Information"),
+	(204, NO_CONTENT, "No Content"),
+	(205, RESET_CONTENT, "Reset Content"),
+	(206, PARTIAL_CONTENT, "Partial Content"),
+	(207, MULTI_STATUS, "Multi-Status"),
+	(208, ALREADY_REPORTED, "Already Reported"),
+	(226, IM_USED, "IM Used"),
+	(300, MULTIPLE_CHOICES, "Multiple Choices"),
+	(301, MOVED_PERMANENTLY, "Moved Permanently"),
+	(302, FOUND, "Found"),
+	(303, SEE_OTHER, "See Other"),
+	(304, NOT_MODIFIED, "Not Modified"),
+	(305, USE_PROXY, "Use Proxy"),
+	(307, TEMPORARY_REDIRECT, "Temporary Redirect"),
+	(308, PERMANENT_REDIRECT, "Permanent Redirect"),
+	(400, BAD_REQUEST, "Bad Request"),
+	(401, UNAUTHORIZED, "Unauthorized"),
+	(402, PAYMENT_REQUIRED, "Payment Required"),
+	(403, FORBIDDEN, "Forbidden"),
+	(404, NOT_FOUND, "Not Found"),
+	(405, METHOD_NOT_ALLOWED, "Method Not Allowed"),
+	(406, NOT_ACCEPTABLE, "Not Acceptable"),
+	(407, PROXY_AUTHENTICATION_REQUIRED, "Proxy Authentication Required"),
+	(408, REQUEST_TIMEOUT, "Request Timeout"),
+	(409, CONFLICT, "Conflict"),
+	(410, GONE, "Gone"),
+	(411, LENGTH_REQUIRED, "Length Required"),
+	(412, PRECONDITION_FAILED, "Precondition Failed"),
+	(413, CONTENT_TOO_LARGE, "Content Too Large"),
+	(414, URI_TOO_LONG, "URI Too Long"),
+	(415, UNSUPPORTED_MEDIA_TYPE, "Unsupported Media Type"),
+	(416, RANGE_NOT_SATISFIABLE, "Range Not Satisfiable"),
+	(417, EXPECTATION_FAILED, "Expectation Failed"),
+	(418, IM_A_TEAPOT, "I'm a teapot"),
+	(421, MISDIRECTED_REQUEST, "Misdirected Request"),
+	(422, UNPROCESSABLE_CONTENT, "Unprocessable Content"),
+	(423, LOCKED, "Locked"),
+	(424, FAILED_DEPENDENCY, "Failed Dependency"),
+	(425, TOO_EARLY, "Too Early"),
+	(426, UPGRADE_REQUIRED, "Upgrade Required"),
+	(428, PRECONDITION_REQUIRED, "Precondition Required"),
+	(429, TOO_MANY_REQUESTS, "Too Many Requests"),
+	(431, REQUEST_HEADER_FIELDS_TOO_LARGE, "Request Header Fields Too Large"),
+	(451, UNAVAILABLE_FOR_LEGAL_REASONS, "Unavailable For Legal Reasons"),
+	(500, INTERNAL_SERVER_ERROR, "Internal Server Error"),
+	(501, NOT_IMPLEMENTED, "Not Implemented"),
+	(502, BAD_GATEWAY, "Bad Gateway"),
+	(503, SERVICE_UNAVAILABLE, "Service Unavailable"),
+	(504, GATEWAY_TIMEOUT, "Gateway Timeout"),
+	(505, HTTP_VERSION_NOT_SUPPORTED, "HTTP Version Not Supported"),
+	(506, VARIANT_ALSO_NEGOTIATES, "Variant Also Negotiates"),
+	(507, INSUFFICIENT_STORAGE, "Insufficient Storage"),
+	(508, LOOP_DETECTED, "Loop Detected"),
+	(510, NOT_EXTENDED, "Not Extended"),
+	(511, NETWORK_AUTHENTICATION_REQUIRED, "Network Authentication Required"),
+}
+
+impl Default for StatusCode {
+	fn default() -> Self {
+		Self::OK
+	}
+}
+
+impl FromStr for StatusCode {
+	type Err = Error;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		// a status code is always exactly three digits
+		if s.len() != 3 || !s.bytes().all(|b| b.is_ascii_digit()) {
+			return Err(Error::InvalidFormat);
+		}
+
+		Self::from_u16(s.parse()?)
+	}
+}
+
+/// Shows the code followed by its canonical reason phrase, if it has one.
+impl fmt::Display for StatusCode {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self.canonical_reason() {
+			Some(reason) => write!(f, "{} {reason}", self.0),
+			None => write!(f, "{}", self.0),
+		}
+	}
+}
+
+impl PartialEq<u16> for StatusCode {
+	fn eq(&self, other: &u16) -> bool {
+		self.0 == *other
+	}
+}
+
+impl PartialEq<StatusCode> for u16 {
+	fn eq(&self, other: &StatusCode) -> bool {
+		*self == other.0
+	}
+}
+
+impl From<StatusCode> for u16 {
+	fn from(status: StatusCode) -> Self {
+		status.0
+	}
+}
+
+impl TryFrom<u16> for StatusCode {
+	type Error = Error;
+
+	fn try_from(code: u16) -> Result<Self, Self::Error> {
+		Self::from_u16(code)
+	}
+}
+
+#[allow(clippy::module_name_repetitions)]
+pub trait IntoStatusCode {
+	/// # Errors
+	/// - If the code is not in the range 100–999.
+	fn into_status_code(self) -> Result<StatusCode, Error>;
+}
+
+impl IntoStatusCode for StatusCode {
+	fn into_status_code(self) -> Result<StatusCode, Error> {
+		Ok(self)
+	}
+}
+
+impl IntoStatusCode for u16 {
+	fn into_status_code(self) -> Result<StatusCode, Error> {
+		StatusCode::from_u16(self)
+	}
+}
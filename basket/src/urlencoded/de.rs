@@ -0,0 +1,437 @@
+use std::collections::HashMap;
+
+use serde::{
+	de::{self, value::StringDeserializer, DeserializeSeed, IntoDeserializer, Visitor},
+	forward_to_deserialize_any,
+};
+
+use crate::Error;
+
+/// A tree of values built from bracketed keys, such as `user[name]=john`.
+#[derive(Debug)]
+pub(crate) enum Node {
+	/// Every value sent for the key, in order.
+	Leaf(Vec<String>),
+	Map(Entries),
+}
+
+/// The nested keys of a node in the order they were first sent, indexed by
+/// key so that each pair is inserted in constant time.
+#[derive(Debug, Default)]
+pub(crate) struct Entries {
+	list: Vec<(String, Node)>,
+	index: HashMap<String, usize>,
+}
+
+impl Node {
+	/// Builds a tree from key-value pairs.
+	///
+	/// A trailing `[]` in a key is ignored, so `tags[]=a` is treated the
+	/// same as `tags=a`.
+	pub(crate) fn from_pairs<I, K, V>(pairs: I) -> Result<Self, Error>
+	where
+		I: IntoIterator<Item = (K, V)>,
+		K: AsRef<str>,
+		V: Into<String>,
+	{
+		let mut root = Self::Map(Entries::default());
+
+		for (key, value) in pairs {
+			let path = parse_key(key.as_ref());
+
+			root.insert(&path, value.into())?;
+		}
+
+		Ok(root)
+	}
+
+	fn insert(&mut self, path: &[&str], value: String) -> Result<(), Error> {
+		let Some((first, rest)) = path.split_first() else {
+			return match self {
+				Self::Leaf(values) => {
+					values.push(value);
+
+					Ok(())
+				}
+				Self::Map(_) => Err(conflict()),
+			};
+		};
+
+		let Self::Map(entries) = self else {
+			return Err(conflict());
+		};
+
+		let index = if let Some(&index) = entries.index.get(*first) {
+			index
+		} else {
+			let node = if rest.is_empty() {
+				Self::Leaf(Vec::new())
+			} else {
+				Self::Map(Entries::default())
+			};
+
+			entries
+				.index
+				.insert((*first).to_string(), entries.list.len());
+			entries.list.push(((*first).to_string(), node));
+			entries.list.len() - 1
+		};
+
+		entries.list[index].1.insert(rest, value)
+	}
+
+	fn into_single(self) -> Result<String, Error> {
+		match self {
+			Self::Leaf(mut values) if values.len() == 1 => Ok(values.remove(0)),
+			Self::Leaf(values) if values.is_empty() => Ok(String::new()),
+			Self::Leaf(_) => Err(Error::UrlEncoded(
+				"expected a single value, found a repeated field".into(),
+			)),
+			Self::Map(_) => Err(Error::UrlEncoded(
+				"expected a value, found nested keys".into(),
+			)),
+		}
+	}
+}
+
+fn conflict() -> Error {
+	Error::UrlEncoded("a key is used both as a value and with nested keys".into())
+}
+
+/// Splits a key like `a[b][c]` into `["a", "b", "c"]`.
+///
+/// Keys that are not well-formed are used as-is.
+fn parse_key(key: &str) -> Vec<&str> {
+	let Some(open) = key.find('[').filter(|&open| open > 0 && key.ends_with(']')) else {
+		return vec![key];
+	};
+
+	let mut path = vec![&key[..open]];
+
+	for segment in key[open + 1..key.len() - 1].split("][") {
+		if segment.contains(['[', ']']) {
+			return vec![key];
+		}
+
+		path.push(segment);
+	}
+
+	// a trailing `[]` appends to the parent key
+	if path.last() == Some(&"") {
+		path.pop();
+	}
+
+	if path.contains(&"") {
+		return vec![key];
+	}
+
+	path
+}
+
+macro_rules! deserialize_parsed {
+	($($method:ident => $visit:ident,)*) => {
+		$(
+			fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+				let value = self.into_single()?;
+				let parsed = value
+					.parse()
+					.map_err(|_| Error::UrlEncoded(format!("invalid value: {value:?}")))?;
+
+				visitor.$visit(parsed)
+			}
+		)*
+	};
+}
+
+impl<'de> de::Deserializer<'de> for Node {
+	type Error = Error;
+
+	fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+		match self {
+			Self::Leaf(values) if values.len() > 1 => Self::Leaf(values).deserialize_seq(visitor),
+			Self::Leaf(_) => visitor.visit_string(self.into_single()?),
+			Self::Map(_) => self.deserialize_map(visitor),
+		}
+	}
+
+	fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+		// html checkboxes are sent as `on` when checked
+		match self.into_single()?.as_str() {
+			"true" | "on" | "1" => visitor.visit_bool(true),
+			"false" | "off" | "0" => visitor.visit_bool(false),
+			value => Err(Error::UrlEncoded(format!("invalid boolean: {value:?}"))),
+		}
+	}
+
+	deserialize_parsed! {
+		deserialize_i8 => visit_i8,
+		deserialize_i16 => visit_i16,
+		deserialize_i32 => visit_i32,
+		deserialize_i64 => visit_i64,
+		deserialize_i128 => visit_i128,
+		deserialize_u8 => visit_u8,
+		deserialize_u16 => visit_u16,
+		deserialize_u32 => visit_u32,
+		deserialize_u64 => visit_u64,
+		deserialize_u128 => visit_u128,
+		deserialize_f32 => visit_f32,
+		deserialize_f64 => visit_f64,
+		deserialize_char => visit_char,
+	}
+
+	fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+		visitor.visit_string(self.into_single()?)
+	}
+
+	fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+		self.deserialize_str(visitor)
+	}
+
+	fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+		visitor.visit_byte_buf(self.into_single()?.into_bytes())
+	}
+
+	fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+		self.deserialize_bytes(visitor)
+	}
+
+	fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+		// empty form inputs are sent with an empty value
+		match &self {
+			Self::Leaf(values) if values.iter().all(String::is_empty) => visitor.visit_none(),
+			_ => visitor.visit_some(self),
+		}
+	}
+
+	fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+		visitor.visit_unit()
+	}
+
+	fn deserialize_unit_struct<V: Visitor<'de>>(
+		self,
+		_name: &'static str,
+		visitor: V,
+	) -> Result<V::Value, Error> {
+		visitor.visit_unit()
+	}
+
+	fn deserialize_newtype_struct<V: Visitor<'de>>(
+		self,
+		_name: &'static str,
+		visitor: V,
+	) -> Result<V::Value, Error> {
+		visitor.visit_newtype_struct(self)
+	}
+
+	fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+		match self {
+			Self::Leaf(values) => visitor.visit_seq(SeqAccess {
+				nodes: values
+					.into_iter()
+					.map(|value| Self::Leaf(vec![value]))
+					.collect::<Vec<_>>()
+					.into_iter(),
+			}),
+			Self::Map(entries) => {
+				let mut indexed = entries
+					.list
+					.into_iter()
+					.map(|(key, node)| {
+						key.parse::<usize>()
+							.map(|index| (index, node))
+							.map_err(|_| Error::UrlEncoded(format!("invalid index: {key:?}")))
+					})
+					.collect::<Result<Vec<_>, _>>()?;
+
+				indexed.sort_by_key(|(index, _)| *index);
+
+				visitor.visit_seq(SeqAccess {
+					nodes: indexed
+						.into_iter()
+						.map(|(_, node)| node)
+						.collect::<Vec<_>>()
+						.into_iter(),
+				})
+			}
+		}
+	}
+
+	fn deserialize_tuple<V: Visitor<'de>>(
+		self,
+		_len: usize,
+		visitor: V,
+	) -> Result<V::Value, Error> {
+		self.deserialize_seq(visitor)
+	}
+
+	fn deserialize_tuple_struct<V: Visitor<'de>>(
+		self,
+		_name: &'static str,
+		_len: usize,
+		visitor: V,
+	) -> Result<V::Value, Error> {
+		self.deserialize_seq(visitor)
+	}
+
+	fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+		match self {
+			Self::Map(entries) => visitor.visit_map(MapAccess {
+				entries: entries.list.into_iter(),
+				value: None,
+			}),
+			Self::Leaf(_) => Err(Error::UrlEncoded(
+				"expected nested keys, found a value".into(),
+			)),
+		}
+	}
+
+	fn deserialize_struct<V: Visitor<'de>>(
+		self,
+		_name: &'static str,
+		_fields: &'static [&'static str],
+		visitor: V,
+	) -> Result<V::Value, Error> {
+		self.deserialize_map(visitor)
+	}
+
+	fn deserialize_enum<V: Visitor<'de>>(
+		self,
+		_name: &'static str,
+		_variants: &'static [&'static str],
+		visitor: V,
+	) -> Result<V::Value, Error> {
+		// only unit variants can be represented by a single value
+		let variant: StringDeserializer<Error> = self.into_single()?.into_deserializer();
+
+		visitor.visit_enum(variant)
+	}
+
+	fn deserialize_identifier<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+		self.deserialize_str(visitor)
+	}
+
+	fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+		visitor.visit_unit()
+	}
+}
+
+struct SeqAccess {
+	nodes: std::vec::IntoIter<Node>,
+}
+
+impl<'de> de::SeqAccess<'de> for SeqAccess {
+	type Error = Error;
+
+	fn next_element_seed<T: DeserializeSeed<'de>>(
+		&mut self,
+		seed: T,
+	) -> Result<Option<T::Value>, Error> {
+		self.nodes
+			.next()
+			.map(|node| seed.deserialize(node))
+			.transpose()
+	}
+
+	fn size_hint(&self) -> Option<usize> {
+		Some(self.nodes.len())
+	}
+}
+
+struct MapAccess {
+	entries: std::vec::IntoIter<(String, Node)>,
+	value: Option<Node>,
+}
+
+impl<'de> de::MapAccess<'de> for MapAccess {
+	type Error = Error;
+
+	fn next_key_seed<K: DeserializeSeed<'de>>(
+		&mut self,
+		seed: K,
+	) -> Result<Option<K::Value>, Error> {
+		let Some((key, value)) = self.entries.next() else {
+			return Ok(None);
+		};
+
+		self.value = Some(value);
+		seed.deserialize(Key(key)).map(Some)
+	}
+
+	fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Error> {
+		let value = self
+			.value
+			.take()
+			.ok_or_else(|| Error::UrlEncoded("value requested before its key".into()))?;
+
+		seed.deserialize(value)
+	}
+
+	fn size_hint(&self) -> Option<usize> {
+		Some(self.entries.len())
+	}
+}
+
+macro_rules! deserialize_key_parsed {
+	($($method:ident,)*) => {
+		$(
+			fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+				Node::Leaf(vec![self.0]).$method(visitor)
+			}
+		)*
+	};
+}
+
+/// Deserializes map keys, which may be parsed into numbers for maps with integer keys.
+struct Key(String);
+
+impl<'de> de::Deserializer<'de> for Key {
+	type Error = Error;
+
+	fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+		visitor.visit_string(self.0)
+	}
+
+	fn deserialize_newtype_struct<V: Visitor<'de>>(
+		self,
+		_name: &'static str,
+		visitor: V,
+	) -> Result<V::Value, Error> {
+		visitor.visit_newtype_struct(self)
+	}
+
+	fn deserialize_enum<V: Visitor<'de>>(
+		self,
+		_name: &'static str,
+		_variants: &'static [&'static str],
+		visitor: V,
+	) -> Result<V::Value, Error> {
+		let variant: StringDeserializer<Error> = self.0.into_deserializer();
+
+		visitor.visit_enum(variant)
+	}
+
+	fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+		Node::Leaf(vec![self.0]).deserialize_bool(visitor)
+	}
+
+	deserialize_key_parsed! {
+		deserialize_i8,
+		deserialize_i16,
+		deserialize_i32,
+		deserialize_i64,
+		deserialize_i128,
+		deserialize_u8,
+		deserialize_u16,
+		deserialize_u32,
+		deserialize_u64,
+		deserialize_u128,
+		deserialize_f32,
+		deserialize_f64,
+		deserialize_char,
+	}
+
+	forward_to_deserialize_any! {
+		str string bytes byte_buf option unit unit_struct seq tuple
+		tuple_struct map struct identifier ignored_any
+	}
+}
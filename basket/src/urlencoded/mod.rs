@@ -0,0 +1,83 @@
+mod de;
+mod ser;
+
+use serde::{de::DeserializeOwned, Serialize};
+use url::form_urlencoded;
+
+use crate::Error;
+
+/// Serializes a value into `application/x-www-form-urlencoded` pairs.
+///
+/// Nested structs and maps are written with bracketed keys, such as
+/// `user[name]=john`, and sequences of values are written as repeated keys,
+/// such as `tag=a&tag=b`. Sequences of structs or sequences use their index
+/// as the key, such as `items[0][name]=apple`.
+///
+/// Empty sequences produce no pairs, so they need `#[serde(default)]` to be
+/// deserialized again.
+///
+/// # Errors
+/// - If the value is not a struct or map.
+/// - If a map has keys that are not strings or numbers.
+pub fn to_pairs<T: ?Sized + Serialize>(value: &T) -> Result<Vec<(String, String)>, Error> {
+	let ser::Value::Map(entries) = value.serialize(ser::Serializer)? else {
+		return Err(Error::UrlEncoded(
+			"only structs and maps can be serialized".into(),
+		));
+	};
+
+	let mut pairs = Vec::new();
+
+	ser::Value::Map(entries).flatten("", &mut pairs);
+
+	Ok(pairs)
+}
+
+/// Serializes a value into an `application/x-www-form-urlencoded` string.
+///
+/// # Errors
+/// - If the value is not a struct or map.
+/// - If a map has keys that are not strings or numbers.
+pub fn to_string<T: ?Sized + Serialize>(value: &T) -> Result<String, Error> {
+	let pairs = to_pairs(value)?;
+
+	Ok(form_urlencoded::Serializer::new(String::new())
+		.extend_pairs(pairs)
+		.finish())
+}
+
+/// Deserializes a value from decoded key-value pairs.
+///
+/// Accepts the format produced by [`to_pairs`], along with `tag[]=a` for
+/// sequences. Repeated keys can only be deserialized into sequences.
+///
+/// # Errors
+/// - If a key is used both as a value and with nested keys.
+/// - Forwards errors from the value's [`Deserialize`](serde::Deserialize) implementation.
+pub fn from_pairs<T, I, K, V>(pairs: I) -> Result<T, Error>
+where
+	T: DeserializeOwned,
+	I: IntoIterator<Item = (K, V)>,
+	K: AsRef<str>,
+	V: Into<String>,
+{
+	T::deserialize(de::Node::from_pairs(pairs)?)
+}
+
+/// Deserializes a value from an `application/x-www-form-urlencoded` string.
+///
+/// # Errors
+/// - If a key is used both as a value and with nested keys.
+/// - Forwards errors from the value's [`Deserialize`](serde::Deserialize) implementation.
+pub fn from_bytes<T: DeserializeOwned>(input: &[u8]) -> Result<T, Error> {
+	from_pairs(form_urlencoded::parse(input).map(|(key, value)| (key, value.into_owned())))
+}
+
+/// Deserializes a value from an `application/x-www-form-urlencoded` string.
+///
+/// # Errors
+/// - If a key is used both as a value and with nested keys.
+/// - Forwards errors from the value's [`Deserialize`](serde::Deserialize) implementation.
+pub fn from_str<T: DeserializeOwned>(input: &str) -> Result<T, Error> {
+	from_bytes(input.as_bytes())
+}
@@ -0,0 +1,484 @@
+use serde::{
+	ser::{self, Impossible},
+	Serialize,
+};
+
+use crate::Error;
+
+/// An intermediate representation of a serialized value, before it is
+/// flattened into key-value pairs.
+pub(crate) enum Value {
+	Str(String),
+	Seq(Vec<Value>),
+	Map(Vec<(String, Value)>),
+	/// A value that produces no pairs, such as `None`.
+	Skip,
+}
+
+impl Value {
+	fn is_scalar(&self) -> bool {
+		matches!(self, Self::Str(_) | Self::Skip)
+	}
+
+	/// Flattens the value into pairs, using bracketed keys for nested maps.
+	///
+	/// Sequences of scalars are written as repeated keys, and sequences of
+	/// maps or sequences are written with their index as the key.
+	pub(crate) fn flatten(self, prefix: &str, pairs: &mut Vec<(String, String)>) {
+		match self {
+			Self::Str(value) => pairs.push((prefix.to_string(), value)),
+			Self::Skip => {}
+			Self::Seq(values) if values.iter().all(Value::is_scalar) => {
+				for value in values {
+					value.flatten(prefix, pairs);
+				}
+			}
+			Self::Seq(values) => {
+				for (index, value) in values.into_iter().enumerate() {
+					value.flatten(&format!("{prefix}[{index}]"), pairs);
+				}
+			}
+			Self::Map(entries) => {
+				for (key, value) in entries {
+					if prefix.is_empty() {
+						value.flatten(&key, pairs);
+					} else {
+						value.flatten(&format!("{prefix}[{key}]"), pairs);
+					}
+				}
+			}
+		}
+	}
+}
+
+pub(crate) struct Serializer;
+
+macro_rules! serialize_display {
+	($($method:ident($ty:ty),)*) => {
+		$(
+			fn $method(self, value: $ty) -> Result<Value, Error> {
+				Ok(Value::Str(value.to_string()))
+			}
+		)*
+	};
+}
+
+impl ser::Serializer for Serializer {
+	type Ok = Value;
+	type Error = Error;
+
+	type SerializeSeq = SeqSerializer;
+	type SerializeTuple = SeqSerializer;
+	type SerializeTupleStruct = SeqSerializer;
+	type SerializeTupleVariant = VariantSerializer<SeqSerializer>;
+	type SerializeMap = MapSerializer;
+	type SerializeStruct = MapSerializer;
+	type SerializeStructVariant = VariantSerializer<MapSerializer>;
+
+	serialize_display! {
+		serialize_bool(bool),
+		serialize_i8(i8),
+		serialize_i16(i16),
+		serialize_i32(i32),
+		serialize_i64(i64),
+		serialize_i128(i128),
+		serialize_u8(u8),
+		serialize_u16(u16),
+		serialize_u32(u32),
+		serialize_u64(u64),
+		serialize_u128(u128),
+		serialize_f32(f32),
+		serialize_f64(f64),
+		serialize_char(char),
+		serialize_str(&str),
+	}
+
+	fn serialize_bytes(self, value: &[u8]) -> Result<Value, Error> {
+		Ok(Value::Str(String::from_utf8_lossy(value).into_owned()))
+	}
+
+	fn serialize_none(self) -> Result<Value, Error> {
+		Ok(Value::Skip)
+	}
+
+	fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<Value, Error> {
+		value.serialize(self)
+	}
+
+	fn serialize_unit(self) -> Result<Value, Error> {
+		Ok(Value::Str(String::new()))
+	}
+
+	fn serialize_unit_struct(self, _name: &'static str) -> Result<Value, Error> {
+		self.serialize_unit()
+	}
+
+	fn serialize_unit_variant(
+		self,
+		_name: &'static str,
+		_index: u32,
+		variant: &'static str,
+	) -> Result<Value, Error> {
+		Ok(Value::Str(variant.to_string()))
+	}
+
+	fn serialize_newtype_struct<T: ?Sized + Serialize>(
+		self,
+		_name: &'static str,
+		value: &T,
+	) -> Result<Value, Error> {
+		value.serialize(self)
+	}
+
+	fn serialize_newtype_variant<T: ?Sized + Serialize>(
+		self,
+		_name: &'static str,
+		_index: u32,
+		variant: &'static str,
+		value: &T,
+	) -> Result<Value, Error> {
+		Ok(Value::Map(vec![(
+			variant.to_string(),
+			value.serialize(self)?,
+		)]))
+	}
+
+	fn serialize_seq(self, len: Option<usize>) -> Result<SeqSerializer, Error> {
+		Ok(SeqSerializer {
+			values: Vec::with_capacity(len.unwrap_or_default()),
+		})
+	}
+
+	fn serialize_tuple(self, len: usize) -> Result<SeqSerializer, Error> {
+		self.serialize_seq(Some(len))
+	}
+
+	fn serialize_tuple_struct(
+		self,
+		_name: &'static str,
+		len: usize,
+	) -> Result<SeqSerializer, Error> {
+		self.serialize_seq(Some(len))
+	}
+
+	fn serialize_tuple_variant(
+		self,
+		_name: &'static str,
+		_index: u32,
+		variant: &'static str,
+		len: usize,
+	) -> Result<Self::SerializeTupleVariant, Error> {
+		Ok(VariantSerializer {
+			variant,
+			inner: self.serialize_seq(Some(len))?,
+		})
+	}
+
+	fn serialize_map(self, len: Option<usize>) -> Result<MapSerializer, Error> {
+		Ok(MapSerializer {
+			entries: Vec::with_capacity(len.unwrap_or_default()),
+			key: None,
+		})
+	}
+
+	fn serialize_struct(self, _name: &'static str, len: usize) -> Result<MapSerializer, Error> {
+		self.serialize_map(Some(len))
+	}
+
+	fn serialize_struct_variant(
+		self,
+		_name: &'static str,
+		_index: u32,
+		variant: &'static str,
+		len: usize,
+	) -> Result<Self::SerializeStructVariant, Error> {
+		Ok(VariantSerializer {
+			variant,
+			inner: self.serialize_map(Some(len))?,
+		})
+	}
+}
+
+pub(crate) struct SeqSerializer {
+	values: Vec<Value>,
+}
+
+impl ser::SerializeSeq for SeqSerializer {
+	type Ok = Value;
+	type Error = Error;
+
+	fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+		self.values.push(value.serialize(Serializer)?);
+
+		Ok(())
+	}
+
+	fn end(self) -> Result<Value, Error> {
+		Ok(Value::Seq(self.values))
+	}
+}
+
+impl ser::SerializeTuple for SeqSerializer {
+	type Ok = Value;
+	type Error = Error;
+
+	fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+		ser::SerializeSeq::serialize_element(self, value)
+	}
+
+	fn end(self) -> Result<Value, Error> {
+		ser::SerializeSeq::end(self)
+	}
+}
+
+impl ser::SerializeTupleStruct for SeqSerializer {
+	type Ok = Value;
+	type Error = Error;
+
+	fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+		ser::SerializeSeq::serialize_element(self, value)
+	}
+
+	fn end(self) -> Result<Value, Error> {
+		ser::SerializeSeq::end(self)
+	}
+}
+
+pub(crate) struct MapSerializer {
+	entries: Vec<(String, Value)>,
+	key: Option<String>,
+}
+
+impl ser::SerializeMap for MapSerializer {
+	type Ok = Value;
+	type Error = Error;
+
+	fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<(), Error> {
+		self.key = Some(key.serialize(KeySerializer)?);
+
+		Ok(())
+	}
+
+	fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+		let key = self
+			.key
+			.take()
+			.ok_or_else(|| Error::UrlEncoded("value serialized before its key".into()))?;
+
+		self.entries.push((key, value.serialize(Serializer)?));
+
+		Ok(())
+	}
+
+	fn end(self) -> Result<Value, Error> {
+		Ok(Value::Map(self.entries))
+	}
+}
+
+impl ser::SerializeStruct for MapSerializer {
+	type Ok = Value;
+	type Error = Error;
+
+	fn serialize_field<T: ?Sized + Serialize>(
+		&mut self,
+		key: &'static str,
+		value: &T,
+	) -> Result<(), Error> {
+		self.entries
+			.push((key.to_string(), value.serialize(Serializer)?));
+
+		Ok(())
+	}
+
+	fn end(self) -> Result<Value, Error> {
+		ser::SerializeMap::end(self)
+	}
+}
+
+pub(crate) struct VariantSerializer<S> {
+	variant: &'static str,
+	inner: S,
+}
+
+impl ser::SerializeTupleVariant for VariantSerializer<SeqSerializer> {
+	type Ok = Value;
+	type Error = Error;
+
+	fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+		ser::SerializeSeq::serialize_element(&mut self.inner, value)
+	}
+
+	fn end(self) -> Result<Value, Error> {
+		Ok(Value::Map(vec![(
+			self.variant.to_string(),
+			ser::SerializeSeq::end(self.inner)?,
+		)]))
+	}
+}
+
+impl ser::SerializeStructVariant for VariantSerializer<MapSerializer> {
+	type Ok = Value;
+	type Error = Error;
+
+	fn serialize_field<T: ?Sized + Serialize>(
+		&mut self,
+		key: &'static str,
+		value: &T,
+	) -> Result<(), Error> {
+		ser::SerializeStruct::serialize_field(&mut self.inner, key, value)
+	}
+
+	fn end(self) -> Result<Value, Error> {
+		Ok(Value::Map(vec![(
+			self.variant.to_string(),
+			ser::SerializeMap::end(self.inner)?,
+		)]))
+	}
+}
+
+/// Serializes map keys, which must be scalars.
+struct KeySerializer;
+
+impl KeySerializer {
+	fn unsupported<T>() -> Result<T, Error> {
+		Err(Error::UrlEncoded(
+			"map keys must be strings or numbers".into(),
+		))
+	}
+}
+
+macro_rules! serialize_key_display {
+	($($method:ident($ty:ty),)*) => {
+		$(
+			fn $method(self, value: $ty) -> Result<String, Error> {
+				Ok(value.to_string())
+			}
+		)*
+	};
+}
+
+impl ser::Serializer for KeySerializer {
+	type Ok = String;
+	type Error = Error;
+
+	type SerializeSeq = Impossible<String, Error>;
+	type SerializeTuple = Impossible<String, Error>;
+	type SerializeTupleStruct = Impossible<String, Error>;
+	type SerializeTupleVariant = Impossible<String, Error>;
+	type SerializeMap = Impossible<String, Error>;
+	type SerializeStruct = Impossible<String, Error>;
+	type SerializeStructVariant = Impossible<String, Error>;
+
+	serialize_key_display! {
+		serialize_bool(bool),
+		serialize_i8(i8),
+		serialize_i16(i16),
+		serialize_i32(i32),
+		serialize_i64(i64),
+		serialize_i128(i128),
+		serialize_u8(u8),
+		serialize_u16(u16),
+		serialize_u32(u32),
+		serialize_u64(u64),
+		serialize_u128(u128),
+		serialize_f32(f32),
+		serialize_f64(f64),
+		serialize_char(char),
+		serialize_str(&str),
+	}
+
+	fn serialize_bytes(self, value: &[u8]) -> Result<String, Error> {
+		Ok(String::from_utf8_lossy(value).into_owned())
+	}
+
+	fn serialize_none(self) -> Result<String, Error> {
+		Self::unsupported()
+	}
+
+	fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<String, Error> {
+		value.serialize(self)
+	}
+
+	fn serialize_unit(self) -> Result<String, Error> {
+		Self::unsupported()
+	}
+
+	fn serialize_unit_struct(self, _name: &'static str) -> Result<String, Error> {
+		Self::unsupported()
+	}
+
+	fn serialize_unit_variant(
+		self,
+		_name: &'static str,
+		_index: u32,
+		variant: &'static str,
+	) -> Result<String, Error> {
+		Ok(variant.to_string())
+	}
+
+	fn serialize_newtype_struct<T: ?Sized + Serialize>(
+		self,
+		_name: &'static str,
+		value: &T,
+	) -> Result<String, Error> {
+		value.serialize(self)
+	}
+
+	fn serialize_newtype_variant<T: ?Sized + Serialize>(
+		self,
+		_name: &'static str,
+		_index: u32,
+		_variant: &'static str,
+		_value: &T,
+	) -> Result<String, Error> {
+		Self::unsupported()
+	}
+
+	fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Error> {
+		Self::unsupported()
+	}
+
+	fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, Error> {
+		Self::unsupported()
+	}
+
+	fn serialize_tuple_struct(
+		self,
+		_name: &'static str,
+		_len: usize,
+	) -> Result<Self::SerializeTupleStruct, Error> {
+		Self::unsupported()
+	}
+
+	fn serialize_tuple_variant(
+		self,
+		_name: &'static str,
+		_index: u32,
+		_variant: &'static str,
+		_len: usize,
+	) -> Result<Self::SerializeTupleVariant, Error> {
+		Self::unsupported()
+	}
+
+	fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Error> {
+		Self::unsupported()
+	}
+
+	fn serialize_struct(
+		self,
+		_name: &'static str,
+		_len: usize,
+	) -> Result<Self::SerializeStruct, Error> {
+		Self::unsupported()
+	}
+
+	fn serialize_struct_variant(
+		self,
+		_name: &'static str,
+		_index: u32,
+		_variant: &'static str,
+		_len: usize,
+	) -> Result<Self::SerializeStructVariant, Error> {
+		Self::unsupported()
+	}
+}
@@ -0,0 +1,58 @@
+use std::fmt;
+
+use crate::{header, Error, HeaderMap};
+
+/// The HTTP version of a message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum Version {
+	/// HTTP/1.0, where connections close after each response by default
+	/// and bodies cannot be chunked.
+	Http10,
+	#[default]
+	Http11,
+}
+
+impl Version {
+	#[must_use]
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			Self::Http10 => "HTTP/1.0",
+			Self::Http11 => "HTTP/1.1",
+		}
+	}
+
+	/// Converts a byte slice, such as `HTTP/1.1`, to a Version.
+	///
+	/// # Errors
+	/// - If the version is not HTTP/1.0 or HTTP/1.1.
+	pub fn from_bytes(value: &[u8]) -> Result<Version, Error> {
+		match value {
+			b"HTTP/1.0" => Ok(Self::Http10),
+			b"HTTP/1.1" => Ok(Self::Http11),
+			_ => Err(Error::UnsupportedHttp),
+		}
+	}
+
+	/// Returns `true` if a connection stays open after a message with these
+	/// headers, which is the default for HTTP/1.1 but not for HTTP/1.0.
+	#[must_use]
+	pub fn keep_alive(self, headers: &HeaderMap<'_>) -> bool {
+		let has = |option: &str| {
+			headers
+				.get_all(header::CONNECTION)
+				.flat_map(|value| value.split(','))
+				.any(|value| value.trim().eq_ignore_ascii_case(option))
+		};
+
+		match self {
+			Self::Http10 => has("keep-alive"),
+			Self::Http11 => !has("close"),
+		}
+	}
+}
+
+impl fmt::Display for Version {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(self.as_str())
+	}
+}